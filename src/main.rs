@@ -1,13 +1,27 @@
 pub mod parser;
 pub mod scanner;
-use std::{env, io::Write, process};
+use std::{env, io::Write, process, thread};
 
-use parser::{interpreter::Interpreter, parser::Parser};
+use parser::{interpreter::Interpreter, parser::Parser, resolver::Resolver};
 
 fn report_error(line: i32, message: &str) {
     println!("[line {line}] Error: {message}");
 }
+// every script call nests several native frames, so scripts run on a thread with enough
+// stack to reach the interpreter's call depth limit
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 fn main() {
+    let child = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(start)
+        .expect("failed to start the interpreter thread");
+    if child.join().is_err() {
+        process::exit(70);
+    }
+}
+
+fn start() {
     let args: Vec<String> = env::args().collect();
 
     // let expression: Expr = Expr::Binary(Binary {
@@ -68,19 +82,26 @@ fn run_prompt() {
 fn run(source: String) {
     let mut scanner = scanner::scanner::new(source);
 
-    let tokens = scanner.scan_tokens();
+    let tokens = scanner.scan_tokens().clone();
     for t in tokens.iter() {
         println!("{:?}", t);
     }
-    // TO DO - return error from scan_tokens();
+    if scanner.error {
+        return;
+    }
 
-    let mut parser: Parser = Parser::new(tokens.clone());
+    let mut parser: Parser = Parser::new(tokens);
     let expression = match parser.parse() {
         Err(_) => return,
         Ok(e) => e,
     };
 
     let mut interpreter = Interpreter::new();
+    let mut resolver = Resolver::new(&mut interpreter);
+    if resolver.resolve(&expression).is_err() {
+        return;
+    }
+
     match interpreter.interpret(&expression) {
         Err(e) => report_error(e.line, &e.message),
         _ => (),
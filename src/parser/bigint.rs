@@ -0,0 +1,492 @@
+use std::{cmp::Ordering, fmt};
+
+// Arbitrary-precision integer stored as a sign and a little-endian magnitude
+// in base 2^32. It is kept normalized: the magnitude has no leading zero
+// limbs and zero is never negative, so the derived `PartialEq` and `Hash`
+// compare values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn from_i64(value: i64) -> BigInt {
+        let magnitude = value.unsigned_abs();
+        return BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32]);
+    }
+
+    // only integral, finite floats have an exact big integer value
+    pub fn from_f64(value: f64) -> Option<BigInt> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        let bits = value.abs().to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return Some(BigInt::from_i64(0));
+        }
+        let mantissa = (bits & ((1u64 << 52) - 1)) | (1u64 << 52);
+        let shift = exponent - 1075;
+        let mut result = if shift >= 0 {
+            BigInt::from_parts(false, vec![mantissa as u32, (mantissa >> 32) as u32])
+                .shifted_left(shift as usize)
+        } else {
+            let m = mantissa >> (-shift);
+            BigInt::from_parts(false, vec![m as u32, (m >> 32) as u32])
+        };
+        result.negative = value < 0.0 && !result.is_zero();
+        return Some(result);
+    }
+
+    // parses an optionally negative run of decimal digits
+    pub fn parse(text: &str) -> Option<BigInt> {
+        return BigInt::parse_radix(text, 10);
+    }
+
+    pub fn parse_radix(text: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude: Vec<u32> = vec![];
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            magnitude = mul_small_add(&magnitude, radix, digit);
+        }
+        return Some(BigInt::from_parts(negative, magnitude));
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        trim(&mut magnitude);
+        let negative = negative && !magnitude.is_empty();
+        return BigInt {
+            negative,
+            magnitude,
+        };
+    }
+
+    pub fn is_zero(&self) -> bool {
+        return self.magnitude.is_empty();
+    }
+
+    pub fn is_negative(&self) -> bool {
+        return self.negative;
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let mut value: u64 = 0;
+        for (i, limb) in self.magnitude.iter().enumerate() {
+            value |= (*limb as u64) << (32 * i);
+        }
+        if self.negative {
+            if value <= 1u64 << 63 {
+                return Some((value as i64).wrapping_neg());
+            }
+            return None;
+        }
+        if value <= i64::MAX as u64 {
+            return Some(value as i64);
+        }
+        return None;
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let mut value = 0.0;
+        for limb in self.magnitude.iter().rev() {
+            value = value * 4294967296.0 + *limb as f64;
+        }
+        if self.negative {
+            return -value;
+        }
+        return value;
+    }
+
+    pub fn negated(&self) -> BigInt {
+        return BigInt::from_parts(!self.negative, self.magnitude.clone());
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitudes(&self.magnitude, &other.magnitude),
+            );
+        }
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                return BigInt::from_parts(
+                    other.negative,
+                    sub_magnitudes(&other.magnitude, &self.magnitude),
+                )
+            }
+            _ => {
+                return BigInt::from_parts(
+                    self.negative,
+                    sub_magnitudes(&self.magnitude, &other.magnitude),
+                )
+            }
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        return self.add(&other.negated());
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, a) in self.magnitude.iter().enumerate() {
+            let mut carry: u64 = 0;
+            for (j, b) in other.magnitude.iter().enumerate() {
+                let current = product[i + j] as u64 + (*a as u64) * (*b as u64) + carry;
+                product[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        return BigInt::from_parts(self.negative != other.negative, product);
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        return result;
+    }
+
+    // Truncating division like i64: the quotient rounds towards zero and the
+    // remainder takes the sign of the dividend. Returns None when dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = divide_magnitudes(&self.magnitude, &other.magnitude);
+        return Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ));
+    }
+
+    // bitwise operations act on the infinite two's complement representation,
+    // like they do on i64
+    pub fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+        let a = self.twos_complement(len);
+        let b = other.twos_complement(len);
+        let limbs: Vec<u32> = a.iter().zip(b.iter()).map(|(x, y)| op(*x, *y)).collect();
+        return BigInt::from_twos_complement(limbs);
+    }
+
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (value, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = value;
+                carry = overflow;
+            }
+        }
+        return limbs;
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> BigInt {
+        let negative = limbs.last().is_some_and(|limb| limb >> 31 == 1);
+        if negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (value, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = value;
+                carry = overflow;
+            }
+        }
+        return BigInt::from_parts(negative, limbs);
+    }
+
+    pub fn shl(&self, bits: usize) -> BigInt {
+        return self.shifted_left(bits);
+    }
+
+    // an arithmetic shift, so negative numbers round towards negative infinity
+    pub fn shr(&self, bits: usize) -> BigInt {
+        if self.negative {
+            // -((|x| - 1) >> bits) - 1
+            let one = BigInt::from_i64(1);
+            let shifted = self.negated().sub(&one).shifted_right(bits);
+            return shifted.negated().sub(&one);
+        }
+        return self.shifted_right(bits);
+    }
+
+    fn shifted_right(&self, bits: usize) -> BigInt {
+        let skip = bits / 32;
+        let shift = bits % 32;
+        let mut magnitude: Vec<u32> = vec![];
+        for i in skip..self.magnitude.len() {
+            let mut limb = self.magnitude[i] >> shift;
+            if shift > 0 && i + 1 < self.magnitude.len() {
+                limb |= self.magnitude[i + 1] << (32 - shift);
+            }
+            magnitude.push(limb);
+        }
+        return BigInt::from_parts(self.negative, magnitude);
+    }
+
+    fn shifted_left(&self, bits: usize) -> BigInt {
+        let mut magnitude = vec![0u32; bits / 32];
+        let shift = bits % 32;
+        let mut carry: u32 = 0;
+        for limb in self.magnitude.iter() {
+            if shift == 0 {
+                magnitude.push(*limb);
+            } else {
+                magnitude.push((limb << shift) | carry);
+                carry = limb >> (32 - shift);
+            }
+        }
+        magnitude.push(carry);
+        return BigInt::from_parts(self.negative, magnitude);
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => return Ordering::Greater,
+            (true, false) => return Ordering::Less,
+            (false, false) => return compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => return compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // peel off nine decimal digits at a time, least significant first
+        let mut chunks: Vec<u32> = vec![];
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let (quotient, remainder) = divide_small(&rest, 1_000_000_000);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks[chunks.len() - 1])?;
+        for chunk in chunks.iter().rev().skip(1) {
+            write!(f, "{:09}", chunk)?;
+        }
+        return Ok(());
+    }
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for (x, y) in a.iter().rev().zip(b.iter().rev()) {
+        if x != y {
+            return x.cmp(y);
+        }
+    }
+    return Ordering::Equal;
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum: Vec<u32> = vec![];
+    let mut carry: u64 = 0;
+    for i in 0..a.len().max(b.len()) {
+        let current = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(current as u32);
+        carry = current >> 32;
+    }
+    sum.push(carry as u32);
+    trim(&mut sum);
+    return sum;
+}
+
+// requires a >= b
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference: Vec<u32> = vec![];
+    let mut borrow: i64 = 0;
+    for (i, limb) in a.iter().enumerate() {
+        let mut current = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        if current < 0 {
+            current += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        difference.push(current as u32);
+    }
+    trim(&mut difference);
+    return difference;
+}
+
+fn mul_small_add(a: &[u32], factor: u32, addend: u32) -> Vec<u32> {
+    let mut result: Vec<u32> = vec![];
+    let mut carry: u64 = addend as u64;
+    for limb in a.iter() {
+        let current = *limb as u64 * factor as u64 + carry;
+        result.push(current as u32);
+        carry = current >> 32;
+    }
+    result.push(carry as u32);
+    trim(&mut result);
+    return result;
+}
+
+fn divide_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: u64 = 0;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    return (quotient, remainder as u32);
+}
+
+// schoolbook binary long division; b must not be zero
+fn divide_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if b.len() == 1 {
+        let (quotient, remainder) = divide_small(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..a.len() * 32).rev() {
+        remainder = mul_small_add(&remainder, 2, (a[bit / 32] >> (bit % 32)) & 1);
+        if compare_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    trim(&mut quotient);
+    return (quotient, remainder);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigInt;
+
+    fn big(text: &str) -> BigInt {
+        return BigInt::parse(text).unwrap();
+    }
+
+    fn div_rem(a: &str, b: &str) -> (String, String) {
+        let (quotient, remainder) = big(a).div_rem(&big(b)).unwrap();
+        return (quotient.to_string(), remainder.to_string());
+    }
+
+    #[test]
+    fn div_rem_truncates_and_keeps_the_dividend_sign() {
+        let pair = |q: &str, r: &str| (q.to_string(), r.to_string());
+        assert_eq!(div_rem("7", "2"), pair("3", "1"));
+        assert_eq!(div_rem("-7", "2"), pair("-3", "-1"));
+        assert_eq!(div_rem("7", "-2"), pair("-3", "1"));
+        assert_eq!(div_rem("-7", "-2"), pair("3", "-1"));
+        assert_eq!(div_rem("-6", "3"), pair("-2", "0"));
+        assert_eq!(
+            div_rem("-100000000000000000000000000000", "30000000000000000001"),
+            pair("-3333333333", "-9999999996666666667")
+        );
+        assert!(big("1").div_rem(&big("0")).is_none());
+    }
+
+    #[test]
+    fn bitwise_uses_twos_complement_for_negatives() {
+        let and = |a: &str, b: &str| big(a).bitwise(&big(b), |x, y| x & y).to_string();
+        let or = |a: &str, b: &str| big(a).bitwise(&big(b), |x, y| x | y).to_string();
+        let xor = |a: &str, b: &str| big(a).bitwise(&big(b), |x, y| x ^ y).to_string();
+        assert_eq!(and("-1", "12345"), "12345");
+        assert_eq!(and("-6", "7"), "2");
+        assert_eq!(or("-8", "3"), "-5");
+        assert_eq!(xor("-1", "5"), "-6");
+        assert_eq!(
+            and("-18446744073709551616", "36893488147419103231"),
+            "18446744073709551616"
+        );
+    }
+
+    #[test]
+    fn shr_rounds_towards_negative_infinity() {
+        assert_eq!(big("-1").shr(1).to_string(), "-1");
+        assert_eq!(big("-5").shr(1).to_string(), "-3");
+        assert_eq!(big("-4").shr(1).to_string(), "-2");
+        assert_eq!(big("5").shr(1).to_string(), "2");
+        assert_eq!(big("-18446744073709551617").shr(64).to_string(), "-2");
+        assert_eq!(big("-1").shl(70).shr(70).to_string(), "-1");
+    }
+
+    #[test]
+    fn to_i64_at_the_limits() {
+        assert_eq!(BigInt::from_i64(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(
+            BigInt::from_i64(i64::MIN).to_string(),
+            "-9223372036854775808"
+        );
+    }
+
+    #[test]
+    fn from_f64_accepts_only_finite_integral_values() {
+        assert_eq!(BigInt::from_f64(0.0), Some(BigInt::from_i64(0)));
+        assert_eq!(BigInt::from_f64(-0.0), Some(BigInt::from_i64(0)));
+        assert_eq!(BigInt::from_f64(-42.0), Some(BigInt::from_i64(-42)));
+        assert_eq!(BigInt::from_f64(1e20), Some(big("100000000000000000000")));
+        assert_eq!(BigInt::from_f64(1.5), None);
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+        assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn display_zero_pads_inner_chunks() {
+        assert_eq!(big("0").to_string(), "0");
+        assert_eq!(big("1000000000").to_string(), "1000000000");
+        assert_eq!(
+            big("1000000000000000001").to_string(),
+            "1000000000000000001"
+        );
+        assert_eq!(
+            big("-5000000000000000070").to_string(),
+            "-5000000000000000070"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::scanner::token::{LiteralType, TokenType};
+
+use super::{
+    class::Instance,
+    interpreter::{Flow, Interpreter, RuntimeError},
+    statements::{self, Environment},
+};
+
+pub trait Callable {
+    fn arity(&self) -> usize;
+    // `line` is the line of the call's closing paren, used for error reporting
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LiteralType>,
+        line: i32,
+    ) -> Result<LiteralType, RuntimeError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub declaration: statements::Function,
+    // the scope the function was declared in, kept alive for as long as the function is
+    pub closure: Rc<RefCell<Environment>>,
+    pub is_initializer: bool,
+}
+
+impl Function {
+    // the bound copy closes over a scope holding `this`
+    pub fn bind(&self, instance: Rc<RefCell<Instance>>) -> Function {
+        let mut environment = Environment::new_with_enclosing(self.closure.clone());
+        environment.define(&"this".to_string(), LiteralType::Instance(instance));
+        return Function {
+            declaration: self.declaration.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        // anonymous functions carry their 'fun' or '=>' token in place of a name
+        if self.declaration.name.token_type == TokenType::IDENTIFIER {
+            return format!("<fn {}>", self.declaration.name.lexeme);
+        }
+        return "<fn>".to_string();
+    }
+
+    fn this(&self) -> LiteralType {
+        return self
+            .closure
+            .borrow()
+            .values
+            .get("this")
+            .cloned()
+            .unwrap_or(LiteralType::Null);
+    }
+}
+
+impl Callable for Function {
+    fn arity(&self) -> usize {
+        return self.declaration.params.len();
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LiteralType>,
+        _line: i32,
+    ) -> Result<LiteralType, RuntimeError> {
+        let mut environment = Environment::new_with_enclosing(self.closure.clone());
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(&param.lexeme, argument);
+        }
+        let flow = interpreter.execute_block(&self.declaration.body, environment)?;
+        // an initializer always hands back the instance, even on an early 'return;'
+        if self.is_initializer {
+            return Ok(self.this());
+        }
+        match flow {
+            Flow::Return(value) => return Ok(value),
+            _ => return Ok(LiteralType::Null),
+        }
+    }
+}
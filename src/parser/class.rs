@@ -0,0 +1,87 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::scanner::token::{LiteralType, Token};
+
+use super::{
+    callable::{Callable, Function},
+    interpreter::{Interpreter, RuntimeError},
+};
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(method.clone());
+        }
+        if let Some(superclass) = &self.superclass {
+            return superclass.find_method(name);
+        }
+        return None;
+    }
+}
+
+// implemented on the Rc so that new instances can point back at their class
+impl Callable for Rc<Class> {
+    fn arity(&self) -> usize {
+        match self.find_method("init") {
+            Some(initializer) => return initializer.arity(),
+            None => return 0,
+        }
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<LiteralType>,
+        line: i32,
+    ) -> Result<LiteralType, RuntimeError> {
+        let instance = Rc::new(RefCell::new(Instance {
+            class: self.clone(),
+            fields: HashMap::new(),
+        }));
+        if let Some(initializer) = self.find_method("init") {
+            initializer
+                .bind(instance.clone())
+                .call(interpreter, arguments, line)?;
+        }
+        return Ok(LiteralType::Instance(instance));
+    }
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, LiteralType>,
+}
+
+impl Instance {
+    // fields shadow methods; methods come back bound to the instance they were read from
+    pub fn get(
+        instance: &Rc<RefCell<Instance>>,
+        name: &Token,
+    ) -> Result<LiteralType, RuntimeError> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(m) => return Ok(LiteralType::Function(Rc::new(m.bind(instance.clone())))),
+            None => {
+                return Err(RuntimeError::error(
+                    name.line,
+                    format!("Undefined property '{}'.", name.lexeme),
+                ))
+            }
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: LiteralType) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
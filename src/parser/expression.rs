@@ -1,6 +1,6 @@
 use crate::scanner::token::{LiteralType, Token};
 
-use super::{interpreter::RuntimeError, statements::Var};
+use super::{interpreter::RuntimeError, statements::Function};
 #[derive(Debug, Clone)]
 pub enum Expr {
     Binary(Binary),
@@ -10,6 +10,22 @@ pub enum Expr {
     Variable(Variable),
     Assign(Assign),
     Logical(Logical),
+    Call(Call),
+    Get(Get),
+    Set(Set),
+    This(This),
+    Super(Super),
+    Lambda(Lambda),
+    List(List),
+    Map(Map),
+    Index(Index),
+    Slice(Slice),
+    Interpolation(Interpolation),
+    Tuple(Tuple),
+    TupleAssign(TupleAssign),
+    Compound(Compound),
+    Conditional(Conditional),
+    SetIndex(SetIndex),
 }
 #[derive(Debug, Clone)]
 pub struct Binary {
@@ -25,8 +41,123 @@ pub struct Logical {
     pub right: Box<Expr>,
 }
 
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct Set {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct This {
+    pub id: usize,
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct Super {
+    pub id: usize,
+    pub keyword: Token,
+    pub method: Token,
+}
+
+// an anonymous function; its declaration is named by the 'fun' or '=>' token
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub declaration: Function,
+}
+
+#[derive(Debug, Clone)]
+pub struct List {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+// `object[start:end]`, either bound may be left out
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+}
+
+// "a ${b} c" alternates literal string segments and embedded expressions
+#[derive(Debug, Clone)]
+pub struct Interpolation {
+    pub parts: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tuple {
+    pub paren: Token,
+    pub elements: Vec<Expr>,
+}
+
+// `(a, b) = value;` assigns the elements of a tuple to variables
+#[derive(Debug, Clone)]
+pub struct TupleAssign {
+    pub paren: Token,
+    pub targets: Vec<Variable>,
+    pub value: Box<Expr>,
+}
+
+// `target op= value`, and `++`/`--` with a value of 1. The target is a
+// Variable, Get or Index expression whose parts are evaluated only once.
+// A postfix update evaluates to the old value.
+#[derive(Debug, Clone)]
+pub struct Compound {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+    pub postfix: bool,
+}
+
+// `condition ? then_branch : else_branch`
+#[derive(Debug, Clone)]
+pub struct Conditional {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SetIndex {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Assign {
+    pub id: usize,
     pub name: Token,
     pub value: Box<Expr>,
 }
@@ -48,6 +179,7 @@ pub struct Grouping {
 
 #[derive(Debug, Clone)]
 pub struct Variable {
+    pub id: usize,
     pub name: Token,
 }
 
@@ -59,6 +191,22 @@ pub trait ExprVisitor<T> {
     fn visit_variable_expr(&mut self, expr: &Variable) -> Result<T, RuntimeError>;
     fn visit_assign_expr(&mut self, expr: &Assign) -> Result<T, RuntimeError>;
     fn visit_logical_expr(&mut self, expr: &Logical) -> Result<T, RuntimeError>;
+    fn visit_call_expr(&mut self, expr: &Call) -> Result<T, RuntimeError>;
+    fn visit_get_expr(&mut self, expr: &Get) -> Result<T, RuntimeError>;
+    fn visit_set_expr(&mut self, expr: &Set) -> Result<T, RuntimeError>;
+    fn visit_this_expr(&mut self, expr: &This) -> Result<T, RuntimeError>;
+    fn visit_super_expr(&mut self, expr: &Super) -> Result<T, RuntimeError>;
+    fn visit_lambda_expr(&mut self, expr: &Lambda) -> Result<T, RuntimeError>;
+    fn visit_list_expr(&mut self, expr: &List) -> Result<T, RuntimeError>;
+    fn visit_map_expr(&mut self, expr: &Map) -> Result<T, RuntimeError>;
+    fn visit_index_expr(&mut self, expr: &Index) -> Result<T, RuntimeError>;
+    fn visit_slice_expr(&mut self, expr: &Slice) -> Result<T, RuntimeError>;
+    fn visit_interpolation_expr(&mut self, expr: &Interpolation) -> Result<T, RuntimeError>;
+    fn visit_tuple_expr(&mut self, expr: &Tuple) -> Result<T, RuntimeError>;
+    fn visit_tuple_assign_expr(&mut self, expr: &TupleAssign) -> Result<T, RuntimeError>;
+    fn visit_compound_expr(&mut self, expr: &Compound) -> Result<T, RuntimeError>;
+    fn visit_conditional_expr(&mut self, expr: &Conditional) -> Result<T, RuntimeError>;
+    fn visit_set_index_expr(&mut self, expr: &SetIndex) -> Result<T, RuntimeError>;
 }
 
 impl Expr {
@@ -71,6 +219,22 @@ impl Expr {
             Expr::Variable(v) => v.accept(visitor),
             Expr::Assign(v) => v.accept(visitor),
             Expr::Logical(v) => v.accept(visitor),
+            Expr::Call(v) => v.accept(visitor),
+            Expr::Get(v) => v.accept(visitor),
+            Expr::Set(v) => v.accept(visitor),
+            Expr::This(v) => v.accept(visitor),
+            Expr::Super(v) => v.accept(visitor),
+            Expr::Lambda(v) => v.accept(visitor),
+            Expr::List(v) => v.accept(visitor),
+            Expr::Map(v) => v.accept(visitor),
+            Expr::Index(v) => v.accept(visitor),
+            Expr::Slice(v) => v.accept(visitor),
+            Expr::Interpolation(v) => v.accept(visitor),
+            Expr::Tuple(v) => v.accept(visitor),
+            Expr::TupleAssign(v) => v.accept(visitor),
+            Expr::Compound(v) => v.accept(visitor),
+            Expr::Conditional(v) => v.accept(visitor),
+            Expr::SetIndex(v) => v.accept(visitor),
         }
     }
 }
@@ -114,6 +278,102 @@ impl Assign {
         return visitor.visit_assign_expr(self);
     }
 }
+
+impl Call {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_call_expr(self);
+    }
+}
+
+impl Get {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_get_expr(self);
+    }
+}
+
+impl Set {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_set_expr(self);
+    }
+}
+
+impl This {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_this_expr(self);
+    }
+}
+
+impl Super {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_super_expr(self);
+    }
+}
+
+impl Lambda {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_lambda_expr(self);
+    }
+}
+
+impl List {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_list_expr(self);
+    }
+}
+
+impl Map {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_map_expr(self);
+    }
+}
+
+impl Index {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_index_expr(self);
+    }
+}
+
+impl Slice {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_slice_expr(self);
+    }
+}
+
+impl Interpolation {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_interpolation_expr(self);
+    }
+}
+
+impl Tuple {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_tuple_expr(self);
+    }
+}
+
+impl TupleAssign {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_tuple_assign_expr(self);
+    }
+}
+
+impl Compound {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_compound_expr(self);
+    }
+}
+
+impl Conditional {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_conditional_expr(self);
+    }
+}
+
+impl SetIndex {
+    pub fn accept<T>(&self, visitor: &mut dyn ExprVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_set_index_expr(self);
+    }
+}
 // pub struct AstPrinter;
 // impl ExprVisitor<String> for AstPrinter {
 //     fn visit_binary_expr(&mut self, expr: &Binary) -> Result<String, RuntimeError> {
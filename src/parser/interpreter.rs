@@ -1,10 +1,16 @@
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, rc::Rc};
+
 use crate::{
     report_error,
-    scanner::token::{LiteralType, TokenType},
+    scanner::token::{LiteralType, Token, TokenType},
 };
 
 use super::{
+    callable::{Callable, Function},
+    class::{Class, Instance},
     expression::{self, Expr, ExprVisitor, Grouping, Literal},
+    map::Map,
+    natives,
     statements::{self, Environment, ExpressionStmt, Stmt, StmtVisitor},
 };
 
@@ -26,8 +32,25 @@ impl RuntimeError {
     }
 }
 
+// How a statement finished executing. Anything other than `Normal` unwinds
+// through the enclosing blocks until a loop with a matching label handles it.
+#[derive(Debug, Clone)]
+pub enum Flow {
+    Normal,
+    Break(Option<String>),
+    Continue(Option<String>),
+    Return(LiteralType),
+}
+
+// calls nested deeper than this report a stack overflow instead of exhausting the native stack
+const MAX_CALL_DEPTH: usize = 1000;
+
 pub struct Interpreter {
-    environment: Environment,
+    environment: Rc<RefCell<Environment>>,
+    globals: Rc<RefCell<Environment>>,
+    // scope depth of every resolved local variable, keyed by expression id
+    locals: HashMap<usize, usize>,
+    call_depth: usize,
 }
 
 impl ExprVisitor<LiteralType> for Interpreter {
@@ -43,11 +66,15 @@ impl ExprVisitor<LiteralType> for Interpreter {
         let right: LiteralType = self.evaluate(&expr.right)?;
 
         match expr.operator.token_type {
-            TokenType::MINUS => {
-                let value = right.get_number(&expr.operator.line)?;
-                return Ok(LiteralType::Float(-value));
-            }
+            TokenType::MINUS => match -right {
+                Ok(t) => return Ok(t),
+                Err(s) => return Err(RuntimeError::error(expr.operator.line, s)),
+            },
             TokenType::BANG => return Ok(LiteralType::Bool(!Interpreter::is_truthful(&right))),
+            TokenType::TILDE => match !right {
+                Ok(t) => return Ok(t),
+                Err(s) => return Err(RuntimeError::error(expr.operator.line, s)),
+            },
             _ => return Err(RuntimeError::error(0, String::from("unreachable "))),
         }
     }
@@ -58,72 +85,63 @@ impl ExprVisitor<LiteralType> for Interpreter {
         let left: LiteralType = self.evaluate(&expr.left)?;
         let right: LiteralType = self.evaluate(&expr.right)?;
 
-        match expr.operator.token_type {
-            TokenType::MINUS => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                return Ok(LiteralType::Float(left_val - right_val));
-            }
-            TokenType::SLASH => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                return Ok(LiteralType::Float(left_val / right_val));
-            }
-            TokenType::STAR => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                return Ok(LiteralType::Float(left_val * right_val));
-            }
-            TokenType::PLUS => {
-                let result = left + right;
-                match result {
-                    Ok(t) => return Ok(t),
-                    Err(s) => return Err(RuntimeError::error(expr.operator.line, s)),
-                }
-            }
+        let result = match expr.operator.token_type {
+            TokenType::MINUS => left - right,
+            TokenType::SLASH => left / right,
+            TokenType::STAR => left * right,
+            TokenType::PERCENT => left % right,
+            TokenType::STAR_STAR => left.pow(right),
+            TokenType::AMPERSAND => left & right,
+            TokenType::PIPE => left | right,
+            TokenType::CARET => left ^ right,
+            TokenType::LESS_LESS => left << right,
+            TokenType::GREATER_GREATER => left >> right,
+            TokenType::PLUS => left + right,
             TokenType::GREATER => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                let comparison = if left_val > right_val { true } else { false };
-                return Ok(LiteralType::Bool(comparison));
+                let ordering = Interpreter::compare(&left, &right, expr.operator.line)?;
+                return Ok(LiteralType::Bool(ordering == Some(Ordering::Greater)));
             }
             TokenType::GREATER_EQUAL => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                let comparison = if left_val >= right_val { true } else { false };
-                return Ok(LiteralType::Bool(comparison));
+                let ordering = Interpreter::compare(&left, &right, expr.operator.line)?;
+                return Ok(LiteralType::Bool(matches!(
+                    ordering,
+                    Some(Ordering::Greater | Ordering::Equal)
+                )));
             }
             TokenType::LESS => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                let comparison = if left_val < right_val { true } else { false };
-                return Ok(LiteralType::Bool(comparison));
+                let ordering = Interpreter::compare(&left, &right, expr.operator.line)?;
+                return Ok(LiteralType::Bool(ordering == Some(Ordering::Less)));
             }
             TokenType::LESS_EQUAL => {
-                let left_val = left.get_number(&expr.operator.line)?;
-                let right_val = right.get_number(&expr.operator.line)?;
-                let comparison = if left_val <= right_val { true } else { false };
-                return Ok(LiteralType::Bool(comparison));
+                let ordering = Interpreter::compare(&left, &right, expr.operator.line)?;
+                return Ok(LiteralType::Bool(matches!(
+                    ordering,
+                    Some(Ordering::Less | Ordering::Equal)
+                )));
             }
             // THIS MAY NOT WORK
             TokenType::BANG_EQUAL => return Ok(LiteralType::Bool(left != right)),
             TokenType::EQUAL_EQUAL => return Ok(LiteralType::Bool(left == right)),
 
             _ => return Err(RuntimeError::error(0, String::from("unreachable "))),
+        };
+        match result {
+            Ok(t) => return Ok(t),
+            Err(s) => return Err(RuntimeError::error(expr.operator.line, s)),
         }
     }
     fn visit_variable_expr(
         &mut self,
         expr: &expression::Variable,
     ) -> Result<LiteralType, RuntimeError> {
-        self.environment.get(expr.name.clone())
+        self.look_up_variable(expr.id, expr.name.clone())
     }
     fn visit_assign_expr(
         &mut self,
         expr: &expression::Assign,
     ) -> Result<LiteralType, RuntimeError> {
         let value = self.evaluate(&expr.value)?;
-        self.environment.assign(expr.name.clone(), value.clone())?;
+        self.assign_variable(expr.id, &expr.name, value.clone())?;
         Ok(value)
     }
 
@@ -149,75 +167,731 @@ impl ExprVisitor<LiteralType> for Interpreter {
             _ => return Err(RuntimeError::error(0, String::from("unreachable "))),
         }
     }
+
+    fn visit_call_expr(&mut self, expr: &expression::Call) -> Result<LiteralType, RuntimeError> {
+        let callee = self.evaluate(&expr.callee)?;
+        let mut arguments: Vec<LiteralType> = vec![];
+        for argument in expr.arguments.iter() {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let function: &dyn Callable = match &callee {
+            LiteralType::Function(f) => f.as_ref(),
+            LiteralType::Class(c) => c,
+            LiteralType::Native(n) => n.as_ref(),
+            _ => {
+                return Err(RuntimeError::error(
+                    expr.paren.line,
+                    "Can only call functions and classes.".to_string(),
+                ))
+            }
+        };
+        if arguments.len() != function.arity() {
+            return Err(RuntimeError::error(
+                expr.paren.line,
+                format!(
+                    "Expected {} arguments but got {}.",
+                    function.arity(),
+                    arguments.len()
+                ),
+            ));
+        }
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::error(
+                expr.paren.line,
+                "Stack overflow.".to_string(),
+            ));
+        }
+        self.call_depth += 1;
+        let result = function.call(self, arguments, expr.paren.line);
+        self.call_depth -= 1;
+        result
+    }
+
+    fn visit_get_expr(&mut self, expr: &expression::Get) -> Result<LiteralType, RuntimeError> {
+        match self.evaluate(&expr.object)? {
+            LiteralType::Instance(instance) => return Instance::get(&instance, &expr.name),
+            _ => {
+                return Err(RuntimeError::error(
+                    expr.name.line,
+                    "Only instances have properties.".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn visit_set_expr(&mut self, expr: &expression::Set) -> Result<LiteralType, RuntimeError> {
+        let instance = match self.evaluate(&expr.object)? {
+            LiteralType::Instance(instance) => instance,
+            _ => {
+                return Err(RuntimeError::error(
+                    expr.name.line,
+                    "Only instances have fields.".to_string(),
+                ))
+            }
+        };
+        let value = self.evaluate(&expr.value)?;
+        instance.borrow_mut().set(&expr.name, value.clone());
+        return Ok(value);
+    }
+
+    fn visit_this_expr(&mut self, expr: &expression::This) -> Result<LiteralType, RuntimeError> {
+        self.look_up_variable(expr.id, expr.keyword.clone())
+    }
+
+    fn visit_lambda_expr(
+        &mut self,
+        expr: &expression::Lambda,
+    ) -> Result<LiteralType, RuntimeError> {
+        let function = Function {
+            declaration: expr.declaration.clone(),
+            closure: self.environment.clone(),
+            is_initializer: false,
+        };
+        return Ok(LiteralType::Function(Rc::new(function)));
+    }
+
+    fn visit_list_expr(&mut self, expr: &expression::List) -> Result<LiteralType, RuntimeError> {
+        let mut elements: Vec<LiteralType> = vec![];
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+        return Ok(LiteralType::List(Rc::new(RefCell::new(elements))));
+    }
+
+    fn visit_interpolation_expr(
+        &mut self,
+        expr: &expression::Interpolation,
+    ) -> Result<LiteralType, RuntimeError> {
+        let mut result = String::new();
+        for part in expr.parts.iter() {
+            let value = self.evaluate(part)?;
+            result.push_str(&Interpreter::stringify(value));
+        }
+        return Ok(LiteralType::String(result));
+    }
+
+    fn visit_tuple_expr(&mut self, expr: &expression::Tuple) -> Result<LiteralType, RuntimeError> {
+        let mut elements: Vec<LiteralType> = vec![];
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+        return Ok(LiteralType::Tuple(Rc::new(elements)));
+    }
+
+    // the whole right-hand side is evaluated before any target is assigned, so `(a, b) = (b, a)` swaps
+    fn visit_tuple_assign_expr(
+        &mut self,
+        expr: &expression::TupleAssign,
+    ) -> Result<LiteralType, RuntimeError> {
+        let value = self.evaluate(&expr.value)?;
+        let elements = Interpreter::destructure(&value, expr.targets.len(), expr.paren.line)?;
+        for (target, element) in expr.targets.iter().zip(elements.iter()) {
+            self.assign_variable(target.id, &target.name, element.clone())?;
+        }
+        return Ok(value);
+    }
+
+    // the parts of the target are evaluated once, before the value
+    fn visit_compound_expr(
+        &mut self,
+        expr: &expression::Compound,
+    ) -> Result<LiteralType, RuntimeError> {
+        let line = expr.operator.line;
+        let (old, new) = match &*expr.target {
+            Expr::Variable(v) => {
+                let old = self.look_up_variable(v.id, v.name.clone())?;
+                let new = self.compound_operation(&expr.operator, old.clone(), &expr.value)?;
+                self.assign_variable(v.id, &v.name, new.clone())?;
+                (old, new)
+            }
+            Expr::Get(g) => {
+                let instance = match self.evaluate(&g.object)? {
+                    LiteralType::Instance(instance) => instance,
+                    _ => {
+                        return Err(RuntimeError::error(
+                            g.name.line,
+                            "Only instances have fields.".to_string(),
+                        ))
+                    }
+                };
+                let old = Instance::get(&instance, &g.name)?;
+                let new = self.compound_operation(&expr.operator, old.clone(), &expr.value)?;
+                instance.borrow_mut().set(&g.name, new.clone());
+                (old, new)
+            }
+            Expr::Index(i) => {
+                let object = self.evaluate(&i.object)?;
+                let index = self.evaluate(&i.index)?;
+                let old = Interpreter::get_index(object.clone(), index.clone(), i.bracket.line)?;
+                let new = self.compound_operation(&expr.operator, old.clone(), &expr.value)?;
+                Interpreter::set_index(object, index, new.clone(), i.bracket.line)?;
+                (old, new)
+            }
+            _ => {
+                return Err(RuntimeError::error(
+                    line,
+                    "Invalid assignment target.".to_string(),
+                ))
+            }
+        };
+        if expr.postfix {
+            return Ok(old);
+        }
+        return Ok(new);
+    }
+
+    fn visit_conditional_expr(
+        &mut self,
+        expr: &expression::Conditional,
+    ) -> Result<LiteralType, RuntimeError> {
+        let condition = self.evaluate(&expr.condition)?;
+        if Interpreter::is_truthful(&condition) {
+            return self.evaluate(&expr.then_branch);
+        }
+        return self.evaluate(&expr.else_branch);
+    }
+
+    fn visit_map_expr(&mut self, expr: &expression::Map) -> Result<LiteralType, RuntimeError> {
+        let mut map = Map::new();
+        for (key, value) in expr.entries.iter() {
+            let key = self.evaluate(key)?;
+            let value = self.evaluate(value)?;
+            if let Err(message) = map.insert(key, value) {
+                return Err(RuntimeError::error(expr.brace.line, message));
+            }
+        }
+        return Ok(LiteralType::Map(Rc::new(RefCell::new(map))));
+    }
+
+    fn visit_index_expr(&mut self, expr: &expression::Index) -> Result<LiteralType, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        return Interpreter::get_index(object, index, expr.bracket.line);
+    }
+
+    fn visit_slice_expr(&mut self, expr: &expression::Slice) -> Result<LiteralType, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let start = match &expr.start {
+            Some(start) => Some(self.evaluate(start)?),
+            None => None,
+        };
+        let end = match &expr.end {
+            Some(end) => Some(self.evaluate(end)?),
+            None => None,
+        };
+        match object {
+            LiteralType::List(list) => {
+                let list = list.borrow();
+                let (start, end) =
+                    Interpreter::slice_bounds(start, end, list.len(), expr.bracket.line)?;
+                let elements = list[start..end].to_vec();
+                return Ok(LiteralType::List(Rc::new(RefCell::new(elements))));
+            }
+            LiteralType::Tuple(tuple) => {
+                let (start, end) =
+                    Interpreter::slice_bounds(start, end, tuple.len(), expr.bracket.line)?;
+                return Ok(LiteralType::Tuple(Rc::new(tuple[start..end].to_vec())));
+            }
+            LiteralType::String(s) => {
+                let (start, end) =
+                    Interpreter::slice_bounds(start, end, s.chars().count(), expr.bracket.line)?;
+                let slice = s.chars().skip(start).take(end - start).collect();
+                return Ok(LiteralType::String(slice));
+            }
+            _ => {
+                return Err(RuntimeError::error(
+                    expr.bracket.line,
+                    "Only lists, tuples and strings can be sliced.".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn visit_set_index_expr(
+        &mut self,
+        expr: &expression::SetIndex,
+    ) -> Result<LiteralType, RuntimeError> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+        Interpreter::set_index(object, index, value.clone(), expr.bracket.line)?;
+        return Ok(value);
+    }
+
+    fn visit_super_expr(&mut self, expr: &expression::Super) -> Result<LiteralType, RuntimeError> {
+        let distance = match self.locals.get(&expr.id) {
+            Some(d) => *d,
+            None => {
+                return Err(RuntimeError::error(
+                    expr.keyword.line,
+                    "Can't use 'super' outside of a class.".to_string(),
+                ))
+            }
+        };
+        let superclass = Environment::get_at(&self.environment, distance, expr.keyword.clone())?;
+        // 'this' is always bound in the scope just inside the one holding 'super'
+        let mut this = expr.keyword.clone();
+        this.lexeme = "this".to_string();
+        let object = Environment::get_at(&self.environment, distance - 1, this)?;
+
+        let method = match &superclass {
+            LiteralType::Class(c) => c.find_method(&expr.method.lexeme),
+            _ => None,
+        };
+        match (method, object) {
+            (Some(m), LiteralType::Instance(instance)) => {
+                return Ok(LiteralType::Function(Rc::new(m.bind(instance))))
+            }
+            _ => {
+                return Err(RuntimeError::error(
+                    expr.method.line,
+                    format!("Undefined property '{}'.", expr.method.lexeme),
+                ))
+            }
+        }
+    }
 }
 
-impl StmtVisitor<()> for Interpreter {
-    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> Result<(), RuntimeError> {
+impl StmtVisitor<Flow> for Interpreter {
+    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> Result<Flow, RuntimeError> {
         self.evaluate(&stmt.expression)?;
-        return Ok(());
+        return Ok(Flow::Normal);
     }
-    fn visit_print_statment(&mut self, stmt: &statements::PrintStmt) -> Result<(), RuntimeError> {
+    fn visit_print_statment(&mut self, stmt: &statements::PrintStmt) -> Result<Flow, RuntimeError> {
         let value = Interpreter::stringify(self.evaluate(&stmt.expression)?);
         println!("{}", value);
-        return Ok(());
+        return Ok(Flow::Normal);
     }
 
-    fn visit_var_statement(&mut self, stmt: &statements::Var) -> Result<(), RuntimeError> {
+    fn visit_var_statement(&mut self, stmt: &statements::Var) -> Result<Flow, RuntimeError> {
         let mut value = LiteralType::Null;
         if let Some(e) = stmt.initializer.clone() {
             value = self.evaluate(&e)?;
         }
-        Ok(self.environment.define(&stmt.name.lexeme, value))
+        self.environment
+            .borrow_mut()
+            .define(&stmt.name.lexeme, value);
+        return Ok(Flow::Normal);
+    }
+    fn visit_destructure_stmt(
+        &mut self,
+        stmt: &statements::Destructure,
+    ) -> Result<Flow, RuntimeError> {
+        let value = self.evaluate(&stmt.initializer)?;
+        let elements = Interpreter::destructure(&value, stmt.names.len(), stmt.paren.line)?;
+        for (name, element) in stmt.names.iter().zip(elements.iter()) {
+            self.environment
+                .borrow_mut()
+                .define(&name.lexeme, element.clone());
+        }
+        return Ok(Flow::Normal);
     }
-    fn visit_block_stmt(&mut self, stmt: &statements::Block) -> Result<(), RuntimeError> {
+    fn visit_block_stmt(&mut self, stmt: &statements::Block) -> Result<Flow, RuntimeError> {
         self.execute_block(
-            stmt.statements.clone(),
+            &stmt.statements,
             Environment::new_with_enclosing(self.environment.clone()),
         )
     }
-    fn visit_if_stmt(&mut self, stmt: &statements::If) -> Result<(), RuntimeError> {
+    fn visit_if_stmt(&mut self, stmt: &statements::If) -> Result<Flow, RuntimeError> {
         let result = self.evaluate(&stmt.condition)?;
         if Interpreter::is_truthful(&result) {
-            stmt.then_branch.accept(self)?;
+            return stmt.then_branch.accept(self);
         } else if let Some(e) = stmt.else_branch.clone() {
-            e.accept(self)?;
+            return e.accept(self);
         }
-        return Ok(());
+        return Ok(Flow::Normal);
+    }
+    fn visit_while_stmt(&mut self, stmt: &statements::While) -> Result<Flow, RuntimeError> {
+        while Interpreter::is_truthful(&self.evaluate(&stmt.condition)?) {
+            let flow = stmt.body.accept(self)?;
+            match flow {
+                Flow::Break(ref target) if Interpreter::targets_loop(target, &stmt.label) => break,
+                Flow::Continue(ref target) if Interpreter::targets_loop(target, &stmt.label) => {
+                    continue
+                }
+                Flow::Normal => (),
+                _ => return Ok(flow),
+            }
+        }
+        return Ok(Flow::Normal);
+    }
+    fn visit_for_stmt(&mut self, stmt: &statements::For) -> Result<Flow, RuntimeError> {
+        // the loop variable lives in its own scope so it is not visible after the loop
+        let previous = self.environment.clone();
+        self.environment = Rc::new(RefCell::new(Environment::new_with_enclosing(
+            previous.clone(),
+        )));
+        let result = self.execute_for(stmt);
+        self.environment = previous;
+        result
+    }
+    fn visit_break_stmt(&mut self, stmt: &statements::Break) -> Result<Flow, RuntimeError> {
+        return Ok(Flow::Break(stmt.label.as_ref().map(|l| l.lexeme.clone())));
+    }
+    fn visit_continue_stmt(&mut self, stmt: &statements::Continue) -> Result<Flow, RuntimeError> {
+        return Ok(Flow::Continue(
+            stmt.label.as_ref().map(|l| l.lexeme.clone()),
+        ));
+    }
+    fn visit_function_stmt(&mut self, stmt: &statements::Function) -> Result<Flow, RuntimeError> {
+        let function = Function {
+            declaration: stmt.clone(),
+            closure: self.environment.clone(),
+            is_initializer: false,
+        };
+        self.environment
+            .borrow_mut()
+            .define(&stmt.name.lexeme, LiteralType::Function(Rc::new(function)));
+        return Ok(Flow::Normal);
+    }
+    fn visit_return_stmt(&mut self, stmt: &statements::Return) -> Result<Flow, RuntimeError> {
+        let mut value = LiteralType::Null;
+        if let Some(e) = &stmt.value {
+            value = self.evaluate(e)?;
+        }
+        return Ok(Flow::Return(value));
+    }
+    fn visit_class_stmt(&mut self, stmt: &statements::Class) -> Result<Flow, RuntimeError> {
+        let mut superclass = None;
+        if let Some(variable) = &stmt.superclass {
+            match self.look_up_variable(variable.id, variable.name.clone())? {
+                LiteralType::Class(c) => superclass = Some(c),
+                _ => {
+                    return Err(RuntimeError::error(
+                        variable.name.line,
+                        "Superclass must be a class.".to_string(),
+                    ))
+                }
+            }
+        }
+
+        // methods of a subclass close over an extra scope that binds 'super'
+        let mut method_environment = self.environment.clone();
+        if let Some(s) = &superclass {
+            let mut environment = Environment::new_with_enclosing(self.environment.clone());
+            environment.define(&"super".to_string(), LiteralType::Class(s.clone()));
+            method_environment = Rc::new(RefCell::new(environment));
+        }
+
+        let mut methods: HashMap<String, Rc<Function>> = HashMap::new();
+        for method in stmt.methods.iter() {
+            let function = Function {
+                declaration: method.clone(),
+                closure: method_environment.clone(),
+                is_initializer: method.name.lexeme == "init",
+            };
+            methods.insert(method.name.lexeme.clone(), Rc::new(function));
+        }
+        let class = Class {
+            name: stmt.name.lexeme.clone(),
+            superclass,
+            methods,
+        };
+        self.environment
+            .borrow_mut()
+            .define(&stmt.name.lexeme, LiteralType::Class(Rc::new(class)));
+        return Ok(Flow::Normal);
     }
 }
 
 impl Interpreter {
     pub fn new() -> Interpreter {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        natives::install(&mut globals.borrow_mut());
         return Interpreter {
-            environment: Environment::new(),
+            environment: globals.clone(),
+            globals,
+            locals: HashMap::new(),
+            call_depth: 0,
         };
     }
+
+    pub fn resolve(&mut self, id: usize, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    fn look_up_variable(&self, id: usize, name: Token) -> Result<LiteralType, RuntimeError> {
+        match self.locals.get(&id) {
+            Some(distance) => Environment::get_at(&self.environment, *distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
     pub fn interpret(&mut self, statements: &Vec<Stmt>) -> Result<(), RuntimeError> {
         for stmt in statements.iter() {
             match stmt {
                 Stmt::Expression(e) => e.accept(self)?,
                 Stmt::Print(e) => e.accept(self)?,
                 Stmt::Var(v) => v.accept(self)?,
+                Stmt::Destructure(v) => v.accept(self)?,
                 Stmt::Block(v) => v.accept(self)?,
                 Stmt::If(v) => v.accept(self)?,
+                Stmt::While(v) => v.accept(self)?,
+                Stmt::For(v) => v.accept(self)?,
+                Stmt::Break(v) => v.accept(self)?,
+                Stmt::Continue(v) => v.accept(self)?,
+                Stmt::Function(v) => v.accept(self)?,
+                Stmt::Return(v) => v.accept(self)?,
+                Stmt::Class(v) => v.accept(self)?,
             };
         }
         return Ok(());
     }
 
-    fn execute_block(
+    pub fn execute_block(
         &mut self,
-        statements: Vec<Stmt>,
+        statements: &[Stmt],
         environment: Environment,
-    ) -> Result<(), RuntimeError> {
+    ) -> Result<Flow, RuntimeError> {
         let previous = self.environment.clone();
-        self.environment = environment;
+        self.environment = Rc::new(RefCell::new(environment));
 
+        let mut result = Ok(Flow::Normal);
         for statement in statements {
-            statement.accept(self)?;
+            result = statement.accept(self);
+            match result {
+                Ok(Flow::Normal) => (),
+                _ => break,
+            }
+        }
+        self.environment = previous;
+        result
+    }
+
+    fn execute_for(&mut self, stmt: &statements::For) -> Result<Flow, RuntimeError> {
+        if let Some(initializer) = &stmt.initializer {
+            initializer.accept(self)?;
+        }
+        loop {
+            if let Some(condition) = &stmt.condition {
+                if !Interpreter::is_truthful(&self.evaluate(condition)?) {
+                    break;
+                }
+            }
+            let flow = stmt.body.accept(self)?;
+            match flow {
+                Flow::Break(ref target) if Interpreter::targets_loop(target, &stmt.label) => break,
+                Flow::Continue(ref target) if Interpreter::targets_loop(target, &stmt.label) => (),
+                Flow::Normal => (),
+                _ => return Ok(flow),
+            }
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    // an unlabeled break/continue always applies to the innermost loop
+    fn targets_loop(target: &Option<String>, label: &Option<Token>) -> bool {
+        match (target, label) {
+            (None, _) => true,
+            (Some(t), Some(l)) => *t == l.lexeme,
+            (Some(_), None) => false,
+        }
+    }
+
+    // ints and floats compare by value; NaN is unordered with everything
+    fn compare(
+        left: &LiteralType,
+        right: &LiteralType,
+        line: i32,
+    ) -> Result<Option<Ordering>, RuntimeError> {
+        left.get_number(&line)?;
+        right.get_number(&line)?;
+        return Ok(left.partial_cmp(right));
+    }
+
+    fn compound_operation(
+        &mut self,
+        operator: &Token,
+        old: LiteralType,
+        value: &Expr,
+    ) -> Result<LiteralType, RuntimeError> {
+        let value = self.evaluate(value)?;
+        let result = match operator.token_type {
+            TokenType::PLUS_EQUAL | TokenType::PLUS_PLUS => old + value,
+            TokenType::MINUS_EQUAL | TokenType::MINUS_MINUS => old - value,
+            TokenType::STAR_EQUAL => old * value,
+            TokenType::SLASH_EQUAL => old / value,
+            TokenType::PERCENT_EQUAL => old % value,
+            _ => return Err(RuntimeError::error(0, String::from("unreachable "))),
+        };
+        match result {
+            Ok(t) => return Ok(t),
+            Err(s) => return Err(RuntimeError::error(operator.line, s)),
+        }
+    }
+
+    fn assign_variable(
+        &mut self,
+        id: usize,
+        name: &Token,
+        value: LiteralType,
+    ) -> Result<(), RuntimeError> {
+        match self.locals.get(&id) {
+            Some(distance) => {
+                Environment::assign_at(&self.environment, *distance, name.clone(), value)
+            }
+            None => self.globals.borrow_mut().assign(name.clone(), value)?,
+        }
+        return Ok(());
+    }
+
+    fn destructure(
+        value: &LiteralType,
+        count: usize,
+        line: i32,
+    ) -> Result<Rc<Vec<LiteralType>>, RuntimeError> {
+        match value {
+            LiteralType::Tuple(elements) => {
+                if elements.len() != count {
+                    return Err(RuntimeError::error(
+                        line,
+                        format!(
+                            "Expected a tuple of {} values but got {}.",
+                            count,
+                            elements.len()
+                        ),
+                    ));
+                }
+                return Ok(elements.clone());
+            }
+            _ => {
+                return Err(RuntimeError::error(
+                    line,
+                    format!("Only tuples can be destructured, got {:?}.", value),
+                ))
+            }
+        }
+    }
+
+    fn get_index(
+        object: LiteralType,
+        index: LiteralType,
+        line: i32,
+    ) -> Result<LiteralType, RuntimeError> {
+        match object {
+            LiteralType::List(list) => {
+                let list = list.borrow();
+                let i = Interpreter::list_index(&index, list.len(), line)?;
+                return Ok(list[i].clone());
+            }
+            LiteralType::Tuple(tuple) => {
+                let i = Interpreter::list_index(&index, tuple.len(), line)?;
+                return Ok(tuple[i].clone());
+            }
+            LiteralType::String(s) => {
+                let i = Interpreter::list_index(&index, s.chars().count(), line)?;
+                return Ok(LiteralType::String(s.chars().skip(i).take(1).collect()));
+            }
+            LiteralType::Map(map) => match map.borrow().get(&index) {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {
+                    return Err(RuntimeError::error(
+                        line,
+                        format!("Undefined key {:?}.", index),
+                    ))
+                }
+                Err(message) => return Err(RuntimeError::error(line, message)),
+            },
+            _ => {
+                return Err(RuntimeError::error(
+                    line,
+                    "Only lists, tuples, maps and strings can be indexed.".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn set_index(
+        object: LiteralType,
+        index: LiteralType,
+        value: LiteralType,
+        line: i32,
+    ) -> Result<(), RuntimeError> {
+        match object {
+            LiteralType::List(list) => {
+                let mut list = list.borrow_mut();
+                let i = Interpreter::list_index(&index, list.len(), line)?;
+                list[i] = value;
+                return Ok(());
+            }
+            LiteralType::Map(map) => match map.borrow_mut().insert(index, value) {
+                Ok(()) => return Ok(()),
+                Err(message) => return Err(RuntimeError::error(line, message)),
+            },
+            LiteralType::String(_) => {
+                return Err(RuntimeError::error(
+                    line,
+                    "Strings are immutable.".to_string(),
+                ))
+            }
+            LiteralType::Tuple(_) => {
+                return Err(RuntimeError::error(
+                    line,
+                    "Tuples are immutable.".to_string(),
+                ))
+            }
+            _ => {
+                return Err(RuntimeError::error(
+                    line,
+                    "Only lists and maps support index assignment.".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn list_index(index: &LiteralType, len: usize, line: i32) -> Result<usize, RuntimeError> {
+        let i = Interpreter::integer_index(index, line)?;
+        if i < 0 || i as usize >= len {
+            return Err(RuntimeError::error(
+                line,
+                format!("Index {} out of bounds for length {}.", i, len),
+            ));
+        }
+        return Ok(i as usize);
+    }
+
+    // a missing start or end means the beginning or the end of the sequence
+    fn slice_bounds(
+        start: Option<LiteralType>,
+        end: Option<LiteralType>,
+        len: usize,
+        line: i32,
+    ) -> Result<(usize, usize), RuntimeError> {
+        let mut bounds = (0, len as i64);
+        if let Some(start) = start {
+            bounds.0 = Interpreter::integer_index(&start, line)?;
+        }
+        if let Some(end) = end {
+            bounds.1 = Interpreter::integer_index(&end, line)?;
+        }
+        let (start, end) = bounds;
+        if start < 0 || end < start || end as usize > len {
+            return Err(RuntimeError::error(
+                line,
+                format!(
+                    "Slice [{}:{}] out of bounds for length {}.",
+                    start, end, len
+                ),
+            ));
+        }
+        return Ok((start as usize, end as usize));
+    }
+
+    fn integer_index(index: &LiteralType, line: i32) -> Result<i64, RuntimeError> {
+        match index {
+            LiteralType::Int(i) => return Ok(*i),
+            // too large to fit in an i64 means out of bounds anyway
+            LiteralType::BigInt(i) => return Ok(i.to_i64().unwrap_or(i64::MAX)),
+            _ => {
+                return Err(RuntimeError::error(
+                    line,
+                    format!("Index must be an integer, got {:?}.", index),
+                ))
+            }
         }
-        self.environment = previous.clone();
-        Ok(())
     }
 
     fn evaluate(&mut self, expr: &Expr) -> Result<LiteralType, RuntimeError> {
@@ -231,12 +905,51 @@ impl Interpreter {
             _ => return true,
         }
     }
-    fn stringify(literal: LiteralType) -> String {
-        match literal {
-            LiteralType::Null => return String::from("nil"),
-            LiteralType::Float(f) => return f.to_string(),
-            LiteralType::Bool(b) => return b.to_string(),
-            LiteralType::String(s) => return s,
-        }
+    pub fn stringify(literal: LiteralType) -> String {
+        return literal.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scanner::scanner;
+
+    use super::{
+        super::{parser::Parser, resolver::Resolver},
+        Interpreter,
+    };
+
+    // runs `source` and returns the printed value of the global variable `name`
+    fn run(source: &str, name: &str) -> String {
+        let mut scanner = scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        Resolver::new(&mut interpreter)
+            .resolve(&statements)
+            .unwrap();
+        interpreter.interpret(&statements).unwrap();
+        let value = interpreter.environment.borrow().values[name].clone();
+        return Interpreter::stringify(value);
+    }
+
+    #[test]
+    fn closures_keep_their_scope_alive_after_the_call_returns() {
+        let source = "
+            fun makeCounter() {
+                var i = 0;
+                fun inc() { i = i + 1; return i; }
+                return inc;
+            }
+            var counter = makeCounter();
+            var other = makeCounter();
+            counter();
+            counter();
+            other();
+            var result = counter();
+            var other_result = other();
+        ";
+        assert_eq!(run(source, "result"), "3");
+        assert_eq!(run(source, "other_result"), "2");
     }
 }
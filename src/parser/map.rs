@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::scanner::token::LiteralType;
+
+use super::bigint::BigInt;
+
+// A value usable as a map key. Equality is the `PartialEq` on `LiteralType`;
+// hashing has to agree with it, so every integral number hashes the same way
+// whether it is an int, a big integer or a float (which also folds -0.0 into
+// 0), other floats are hashed by their bits, and NaN (which is not equal to
+// itself) is rejected.
+#[derive(Debug, Clone)]
+pub struct MapKey(LiteralType);
+
+impl MapKey {
+    pub fn new(value: LiteralType) -> Result<MapKey, String> {
+        match &value {
+            LiteralType::String(_)
+            | LiteralType::Bool(_)
+            | LiteralType::Int(_)
+            | LiteralType::BigInt(_) => return Ok(MapKey(value)),
+            LiteralType::Float(f) => {
+                if f.is_nan() {
+                    return Err(String::from("NaN can't be used as a map key."));
+                }
+                return Ok(MapKey(value));
+            }
+            _ => {
+                return Err(String::from(
+                    "Map keys must be strings, numbers or booleans.",
+                ))
+            }
+        }
+    }
+
+    pub fn value(&self) -> LiteralType {
+        return self.0.clone();
+    }
+}
+
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        return self.0 == other.0;
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.0 {
+            LiteralType::String(s) => {
+                0u8.hash(state);
+                s.hash(state);
+            }
+            LiteralType::Int(i) => hash_integer(&BigInt::from_i64(*i), state),
+            LiteralType::BigInt(i) => hash_integer(i, state),
+            LiteralType::Float(f) => match BigInt::from_f64(*f) {
+                Some(i) => hash_integer(&i, state),
+                None => {
+                    3u8.hash(state);
+                    f.to_bits().hash(state);
+                }
+            },
+            LiteralType::Bool(b) => {
+                2u8.hash(state);
+                b.hash(state);
+            }
+            _ => (),
+        }
+    }
+}
+
+fn hash_integer<H: Hasher>(value: &BigInt, state: &mut H) {
+    1u8.hash(state);
+    value.hash(state);
+}
+
+// Keeps entries in insertion order; `index` points from each key to its entry.
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(MapKey, LiteralType)>,
+    index: HashMap<MapKey, usize>,
+}
+
+impl Map {
+    pub fn new() -> Map {
+        return Map::default();
+    }
+
+    pub fn get(&self, key: &LiteralType) -> Result<Option<LiteralType>, String> {
+        let key = MapKey::new(key.clone())?;
+        return Ok(self.index.get(&key).map(|i| self.entries[*i].1.clone()));
+    }
+
+    pub fn insert(&mut self, key: LiteralType, value: LiteralType) -> Result<(), String> {
+        let key = MapKey::new(key)?;
+        match self.index.get(&key) {
+            Some(i) => self.entries[*i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn contains(&self, key: &LiteralType) -> Result<bool, String> {
+        let key = MapKey::new(key.clone())?;
+        return Ok(self.index.contains_key(&key));
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (LiteralType, &LiteralType)> {
+        return self.entries.iter().map(|(k, v)| (k.value(), v));
+    }
+}
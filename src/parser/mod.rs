@@ -1,4 +1,10 @@
+pub mod bigint;
+pub mod callable;
+pub mod class;
 pub mod expression;
 pub mod interpreter;
+pub mod map;
+pub mod natives;
 pub mod parser;
+pub mod resolver;
 pub mod statements;
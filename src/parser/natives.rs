@@ -0,0 +1,170 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::scanner::token::LiteralType;
+
+use super::{
+    bigint::BigInt,
+    callable::Callable,
+    interpreter::{Interpreter, RuntimeError},
+    statements::Environment,
+};
+
+// A function implemented in Rust. Like `impl Add for LiteralType` it reports
+// failures as plain messages, which are turned into a `RuntimeError` at the call's line.
+#[derive(Debug)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(Vec<LiteralType>) -> Result<LiteralType, String>,
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        return self.arity;
+    }
+
+    fn call(
+        &self,
+        _interpreter: &mut Interpreter,
+        arguments: Vec<LiteralType>,
+        line: i32,
+    ) -> Result<LiteralType, RuntimeError> {
+        match (self.function)(arguments) {
+            Ok(value) => return Ok(value),
+            Err(message) => return Err(RuntimeError::error(line, message)),
+        }
+    }
+}
+
+const NATIVES: [NativeFunction; 7] = [
+    NativeFunction {
+        name: "clock",
+        arity: 0,
+        function: clock,
+    },
+    NativeFunction {
+        name: "len",
+        arity: 1,
+        function: len,
+    },
+    NativeFunction {
+        name: "type",
+        arity: 1,
+        function: type_of,
+    },
+    NativeFunction {
+        name: "str",
+        arity: 1,
+        function: str,
+    },
+    NativeFunction {
+        name: "num",
+        arity: 1,
+        function: num,
+    },
+    NativeFunction {
+        name: "has",
+        arity: 2,
+        function: has,
+    },
+    NativeFunction {
+        name: "keys",
+        arity: 1,
+        function: keys,
+    },
+];
+
+pub fn install(globals: &mut Environment) {
+    for native in NATIVES {
+        globals.define(
+            &native.name.to_string(),
+            LiteralType::Native(Rc::new(native)),
+        );
+    }
+}
+
+fn clock(_arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    return Ok(LiteralType::Float(now.as_secs_f64()));
+}
+
+fn len(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    match &arguments[0] {
+        LiteralType::String(s) => return Ok(LiteralType::Int(s.chars().count() as i64)),
+        LiteralType::List(l) => return Ok(LiteralType::Int(l.borrow().len() as i64)),
+        LiteralType::Tuple(t) => return Ok(LiteralType::Int(t.len() as i64)),
+        LiteralType::Map(m) => return Ok(LiteralType::Int(m.borrow().len() as i64)),
+        _ => {
+            return Err(String::from(
+                "len() expects a string, a list, a tuple or a map.",
+            ))
+        }
+    }
+}
+
+fn type_of(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    let name = match &arguments[0] {
+        LiteralType::String(_) => "string",
+        LiteralType::Int(_) | LiteralType::BigInt(_) => "int",
+        LiteralType::Float(_) => "float",
+        LiteralType::Bool(_) => "bool",
+        LiteralType::Function(_) | LiteralType::Native(_) => "function",
+        LiteralType::Class(_) => "class",
+        LiteralType::Instance(_) => "instance",
+        LiteralType::List(_) => "list",
+        LiteralType::Tuple(_) => "tuple",
+        LiteralType::Map(_) => "map",
+        LiteralType::Null => "nil",
+    };
+    return Ok(LiteralType::String(name.to_string()));
+}
+
+fn str(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    let value = arguments.into_iter().next().unwrap_or(LiteralType::Null);
+    return Ok(LiteralType::String(Interpreter::stringify(value)));
+}
+
+fn num(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    match &arguments[0] {
+        LiteralType::Int(_) | LiteralType::BigInt(_) | LiteralType::Float(_) => {
+            return Ok(arguments[0].clone())
+        }
+        LiteralType::String(s) => {
+            if let Ok(i) = s.trim().parse::<i64>() {
+                return Ok(LiteralType::Int(i));
+            }
+            if let Some(i) = BigInt::parse(s.trim()) {
+                return Ok(LiteralType::BigInt(i));
+            }
+            match s.trim().parse::<f64>() {
+                Ok(f) => return Ok(LiteralType::Float(f)),
+                Err(_) => return Err(format!("Can't convert '{}' to a number.", s)),
+            }
+        }
+        _ => return Err(String::from("num() expects a string or a number.")),
+    }
+}
+
+fn has(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    match &arguments[0] {
+        LiteralType::Map(m) => return Ok(LiteralType::Bool(m.borrow().contains(&arguments[1])?)),
+        _ => return Err(String::from("has() expects a map.")),
+    }
+}
+
+// keys in insertion order, which is also the order maps are printed in
+fn keys(arguments: Vec<LiteralType>) -> Result<LiteralType, String> {
+    match &arguments[0] {
+        LiteralType::Map(m) => {
+            let keys: Vec<LiteralType> = m.borrow().entries().map(|(k, _)| k).collect();
+            return Ok(LiteralType::List(Rc::new(RefCell::new(keys))));
+        }
+        _ => return Err(String::from("keys() expects a map.")),
+    }
+}
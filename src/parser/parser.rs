@@ -1,4 +1,4 @@
-use std::vec;
+use std::{rc::Rc, vec};
 
 use crate::{
     parser::expression::Variable,
@@ -7,20 +7,38 @@ use crate::{
 };
 
 use super::{
-    expression::{Assign, Binary, Expr, Grouping, Literal, Logical, Unary},
+    expression::{
+        Assign, Binary, Call, Compound, Conditional, Expr, Get, Grouping, Index, Interpolation,
+        Lambda, List, Literal, Logical, Map, Set, SetIndex, Slice, Super, This, Tuple, TupleAssign,
+        Unary,
+    },
     interpreter::RuntimeError,
-    statements::{Block, ExpressionStmt, If, PrintStmt, Stmt, Var},
+    statements::{
+        Block, Break, Class, Continue, Destructure, ExpressionStmt, For, Function, If, PrintStmt,
+        Return, Stmt, Var, While,
+    },
 };
 use crate::scanner::token::TokenType;
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    // labels of the loops enclosing the statement being parsed, innermost last
+    loops: Vec<Option<String>>,
+    function_depth: usize,
+    // identifies variable expressions for the resolver
+    next_id: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        return Parser { tokens, current: 0 };
+        return Parser {
+            tokens,
+            current: 0,
+            loops: vec![],
+            function_depth: 0,
+            next_id: 0,
+        };
     }
 
     pub fn parse(&mut self) -> Result<Vec<Stmt>, RuntimeError> {
@@ -36,10 +54,169 @@ impl Parser {
         if self.match_token(TokenType::VAR) {
             return self.var_declaration();
         }
+        if self.check(TokenType::FUN) && self.check_next(TokenType::IDENTIFIER) {
+            self.advance();
+            return Ok(Stmt::Function(self.function("function")?));
+        }
+        if self.match_token(TokenType::CLASS) {
+            return self.class_declaration();
+        }
         self.statement()
     }
 
+    fn class_declaration(&mut self) -> Result<Stmt, RuntimeError> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect class name. ".to_string())?;
+        let mut superclass = None;
+        if self.match_token(TokenType::LESS) {
+            let superclass_name = self.consume(
+                TokenType::IDENTIFIER,
+                "Expect superclass name. ".to_string(),
+            )?;
+            superclass = Some(Variable {
+                id: self.next_id(),
+                name: superclass_name,
+            });
+        }
+        self.consume(
+            TokenType::LEFT_BRACE,
+            "Expect '{' before class body. ".to_string(),
+        )?;
+        let mut methods: Vec<Function> = vec![];
+        while !self.check(TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+        self.consume(
+            TokenType::RIGHT_BRACE,
+            "Expect '}' after class body. ".to_string(),
+        )?;
+        return Ok(Stmt::Class(Class {
+            name,
+            superclass,
+            methods,
+        }));
+    }
+
+    fn function(&mut self, kind: &str) -> Result<Function, RuntimeError> {
+        let name = self.consume(TokenType::IDENTIFIER, format!("Expect {} name. ", kind))?;
+        self.consume(
+            TokenType::LEFT_PAREN,
+            format!("Expect '(' after {} name. ", kind),
+        )?;
+        let params = self.parameters()?;
+        self.consume(
+            TokenType::LEFT_BRACE,
+            format!("Expect '{{' before {} body. ", kind),
+        )?;
+        let body = self.function_body()?;
+        return Ok(Function {
+            name,
+            params,
+            body: Rc::new(body),
+        });
+    }
+
+    // parses a parameter list up to and including the closing ')'
+    fn parameters(&mut self) -> Result<Vec<Token>, RuntimeError> {
+        let mut params: Vec<Token> = vec![];
+        if !self.check(TokenType::RIGHT_PAREN) {
+            loop {
+                params.push(
+                    self.consume(TokenType::IDENTIFIER, "Expect parameter name. ".to_string())?,
+                );
+                if !self.match_token(TokenType::COMMA) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            TokenType::RIGHT_PAREN,
+            "Expect ')' after parameters. ".to_string(),
+        )?;
+        return Ok(params);
+    }
+
+    fn lambda(&mut self) -> Result<Expr, RuntimeError> {
+        let keyword = Parser::previous(self.tokens.clone(), self.current);
+        self.consume(
+            TokenType::LEFT_PAREN,
+            "Expect '(' after 'fun'. ".to_string(),
+        )?;
+        let params = self.parameters()?;
+        self.consume(
+            TokenType::LEFT_BRACE,
+            "Expect '{' before function body. ".to_string(),
+        )?;
+        let body = self.function_body()?;
+        return Ok(Expr::Lambda(Lambda {
+            declaration: Function {
+                name: keyword,
+                params,
+                body: Rc::new(body),
+            },
+        }));
+    }
+
+    // (a, b) => a + b, or with a block body (a, b) => { ... }
+    fn arrow_function(&mut self) -> Result<Expr, RuntimeError> {
+        self.consume(TokenType::LEFT_PAREN, "Expect '('. ".to_string())?;
+        let params = self.parameters()?;
+        let arrow = self.consume(
+            TokenType::ARROW,
+            "Expect '=>' after parameters. ".to_string(),
+        )?;
+        let body = if self.match_token(TokenType::LEFT_BRACE) {
+            self.function_body()?
+        } else {
+            let value = self.expression()?;
+            vec![Stmt::Return(Return {
+                keyword: arrow.clone(),
+                value: Some(value),
+            })]
+        };
+        return Ok(Expr::Lambda(Lambda {
+            declaration: Function {
+                name: arrow,
+                params,
+                body: Rc::new(body),
+            },
+        }));
+    }
+
+    // looks past a parenthesized identifier list to see whether a '=>' follows
+    fn is_arrow_function(&self) -> bool {
+        let mut index = self.current + 1;
+        if self.tokens[index].token_type != TokenType::RIGHT_PAREN {
+            loop {
+                if self.tokens[index].token_type != TokenType::IDENTIFIER {
+                    return false;
+                }
+                index += 1;
+                if self.tokens[index].token_type != TokenType::COMMA {
+                    break;
+                }
+                index += 1;
+            }
+            if self.tokens[index].token_type != TokenType::RIGHT_PAREN {
+                return false;
+            }
+        }
+        return self.tokens[index + 1].token_type == TokenType::ARROW;
+    }
+
+    // loops outside the function are not visible to 'break' and 'continue' inside it
+    fn function_body(&mut self) -> Result<Vec<Stmt>, RuntimeError> {
+        let enclosing_loops = std::mem::take(&mut self.loops);
+        self.function_depth += 1;
+        let body = self.block();
+        self.function_depth -= 1;
+        self.loops = enclosing_loops;
+        body
+    }
+
     pub fn var_declaration(&mut self) -> Result<Stmt, RuntimeError> {
+        if self.match_token(TokenType::LEFT_PAREN) {
+            return self.destructure_declaration();
+        }
         let name = self.consume(TokenType::IDENTIFIER, "Expect variable name.".to_string())?;
         let mut initializer = None;
         if self.match_token(TokenType::EQUAL) {
@@ -52,6 +229,36 @@ impl Parser {
         return Ok(Stmt::Var(Var { name, initializer }));
     }
 
+    // after `var (`
+    fn destructure_declaration(&mut self) -> Result<Stmt, RuntimeError> {
+        let paren = Parser::previous(self.tokens.clone(), self.current);
+        let mut names: Vec<Token> = vec![];
+        loop {
+            names.push(self.consume(TokenType::IDENTIFIER, "Expect variable name.".to_string())?);
+            if !self.match_token(TokenType::COMMA) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::RIGHT_PAREN,
+            "Expect ')' after variable names.".to_string(),
+        )?;
+        self.consume(
+            TokenType::EQUAL,
+            "Expect '=' after destructuring pattern.".to_string(),
+        )?;
+        let initializer = self.expression()?;
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after variable declaration.".to_string(),
+        )?;
+        return Ok(Stmt::Destructure(Destructure {
+            paren,
+            names,
+            initializer,
+        }));
+    }
+
     fn statement(&mut self) -> Result<Stmt, RuntimeError> {
         if self.match_token(TokenType::IF) {
             return self.if_statement();
@@ -59,6 +266,24 @@ impl Parser {
         if self.match_token(TokenType::PRINT) {
             return self.print_statement();
         }
+        if self.match_token(TokenType::WHILE) {
+            return self.while_statement(None);
+        }
+        if self.match_token(TokenType::FOR) {
+            return self.for_statement(None);
+        }
+        if self.match_token(TokenType::BREAK) {
+            return self.break_statement();
+        }
+        if self.match_token(TokenType::CONTINUE) {
+            return self.continue_statement();
+        }
+        if self.match_token(TokenType::RETURN) {
+            return self.return_statement();
+        }
+        if self.check(TokenType::IDENTIFIER) && self.check_next(TokenType::COLON) {
+            return self.labeled_statement();
+        }
         if self.match_token(TokenType::LEFT_BRACE) {
             return Ok(Stmt::Block(Block {
                 statements: self.block()?,
@@ -86,6 +311,149 @@ impl Parser {
         }
     }
 
+    fn labeled_statement(&mut self) -> Result<Stmt, RuntimeError> {
+        let label = self.advance();
+        self.advance();
+        if self.loops.contains(&Some(label.lexeme.clone())) {
+            return Err(RuntimeError::error(
+                label.line,
+                format!(
+                    "Label '{}' is already used by an enclosing loop. ",
+                    label.lexeme
+                ),
+            ));
+        }
+        if self.match_token(TokenType::WHILE) {
+            return self.while_statement(Some(label));
+        }
+        if self.match_token(TokenType::FOR) {
+            return self.for_statement(Some(label));
+        }
+        return Err(RuntimeError::error(
+            self.peek().line,
+            "Expect loop after label. ".to_string(),
+        ));
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, RuntimeError> {
+        self.consume(
+            TokenType::LEFT_PAREN,
+            "Expect '(' after while. ".to_string(),
+        )?;
+        let condition = self.expression()?;
+        self.consume(
+            TokenType::RIGHT_PAREN,
+            "Expect ')' after while condition. ".to_string(),
+        )?;
+        let body = self.loop_body(&label)?;
+        return Ok(Stmt::While(While {
+            condition,
+            body: Box::new(body),
+            label,
+        }));
+    }
+
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, RuntimeError> {
+        self.consume(TokenType::LEFT_PAREN, "Expect '(' after for. ".to_string())?;
+        let initializer = if self.match_token(TokenType::SEMICOLON) {
+            None
+        } else if self.match_token(TokenType::VAR) {
+            Some(Box::new(self.var_declaration()?))
+        } else {
+            Some(Box::new(self.expression_statement()?))
+        };
+
+        let mut condition = None;
+        if !self.check(TokenType::SEMICOLON) {
+            condition = Some(self.expression()?);
+        }
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after loop condition. ".to_string(),
+        )?;
+
+        let mut increment = None;
+        if !self.check(TokenType::RIGHT_PAREN) {
+            increment = Some(self.expression()?);
+        }
+        self.consume(
+            TokenType::RIGHT_PAREN,
+            "Expect ')' after for clauses. ".to_string(),
+        )?;
+        let body = self.loop_body(&label)?;
+        return Ok(Stmt::For(For {
+            initializer,
+            condition,
+            increment,
+            body: Box::new(body),
+            label,
+        }));
+    }
+
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt, RuntimeError> {
+        self.loops.push(label.as_ref().map(|l| l.lexeme.clone()));
+        let body = self.statement();
+        self.loops.pop();
+        body
+    }
+
+    fn break_statement(&mut self) -> Result<Stmt, RuntimeError> {
+        let keyword = Parser::previous(self.tokens.clone(), self.current);
+        let label = self.loop_label(&keyword)?;
+        self.consume(TokenType::SEMICOLON, "Expect ';' after break. ".to_string())?;
+        return Ok(Stmt::Break(Break { keyword, label }));
+    }
+
+    fn continue_statement(&mut self) -> Result<Stmt, RuntimeError> {
+        let keyword = Parser::previous(self.tokens.clone(), self.current);
+        let label = self.loop_label(&keyword)?;
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after continue. ".to_string(),
+        )?;
+        return Ok(Stmt::Continue(Continue { keyword, label }));
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, RuntimeError> {
+        let keyword = Parser::previous(self.tokens.clone(), self.current);
+        if self.function_depth == 0 {
+            return Err(RuntimeError::error(
+                keyword.line,
+                "Can't return from top-level code. ".to_string(),
+            ));
+        }
+        let mut value = None;
+        if !self.check(TokenType::SEMICOLON) {
+            value = Some(self.expression()?);
+        }
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after return value. ".to_string(),
+        )?;
+        return Ok(Stmt::Return(Return { keyword, value }));
+    }
+
+    // parses the optional label after 'break'/'continue' and checks that it names an enclosing loop
+    fn loop_label(&mut self, keyword: &Token) -> Result<Option<Token>, RuntimeError> {
+        if self.loops.is_empty() {
+            return Err(RuntimeError::error(
+                keyword.line,
+                format!("Can't use '{}' outside of a loop. ", keyword.lexeme),
+            ));
+        }
+        if !self.match_token(TokenType::IDENTIFIER) {
+            return Ok(None);
+        }
+        let label = Parser::previous(self.tokens.clone(), self.current);
+        if !self.loops.contains(&Some(label.lexeme.clone())) {
+            return Err(RuntimeError::error(
+                label.line,
+                format!("Undefined loop label '{}'. ", label.lexeme),
+            ));
+        }
+        return Ok(Some(label));
+    }
+
     fn block(&mut self) -> Result<Vec<Stmt>, RuntimeError> {
         let mut statments: Vec<Stmt> = vec![];
 
@@ -116,16 +484,72 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr, RuntimeError> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
+
+        let compound_types: Vec<TokenType> = vec![
+            TokenType::PLUS_EQUAL,
+            TokenType::MINUS_EQUAL,
+            TokenType::STAR_EQUAL,
+            TokenType::SLASH_EQUAL,
+            TokenType::PERCENT_EQUAL,
+        ];
+        if self.match_tokens(&compound_types) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let value = self.assignment()?;
+            return Ok(Expr::Compound(Compound {
+                target: Box::new(Parser::update_target(expr, &operator)?),
+                operator,
+                value: Box::new(value),
+                postfix: false,
+            }));
+        }
 
         if self.match_token(TokenType::EQUAL) {
             let equals = Parser::previous(self.tokens.clone(), self.current);
             let value = self.assignment()?;
-            if let Expr::Variable(v) = expr {
-                return Ok(Expr::Assign(Assign {
-                    name: v.name,
-                    value: Box::new(value),
-                }));
+            match expr {
+                Expr::Variable(v) => {
+                    return Ok(Expr::Assign(Assign {
+                        id: self.next_id(),
+                        name: v.name,
+                        value: Box::new(value),
+                    }))
+                }
+                Expr::Get(g) => {
+                    return Ok(Expr::Set(Set {
+                        object: g.object,
+                        name: g.name,
+                        value: Box::new(value),
+                    }))
+                }
+                Expr::Index(i) => {
+                    return Ok(Expr::SetIndex(SetIndex {
+                        object: i.object,
+                        bracket: i.bracket,
+                        index: i.index,
+                        value: Box::new(value),
+                    }))
+                }
+                Expr::Tuple(t) => {
+                    let mut targets: Vec<Variable> = vec![];
+                    for element in t.elements {
+                        match element {
+                            Expr::Variable(v) => targets.push(v),
+                            _ => {
+                                return Err(RuntimeError::error(
+                                    equals.line,
+                                    "Invalid assignment target. ".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    return Ok(Expr::TupleAssign(TupleAssign {
+                        paren: t.paren,
+                        targets,
+                        value: Box::new(value),
+                    }));
+                }
+                _ => (),
             }
             return Err(RuntimeError::error(
                 equals.line,
@@ -135,6 +559,25 @@ impl Parser {
         return Ok(expr);
     }
 
+    // right associative: a ? b : c ? d : e is a ? b : (c ? d : e)
+    fn conditional(&mut self) -> Result<Expr, RuntimeError> {
+        let condition = self.or()?;
+        if self.match_token(TokenType::QUESTION) {
+            let then_branch = self.expression()?;
+            self.consume(
+                TokenType::COLON,
+                "Expect ':' after then branch of conditional expression. ".to_string(),
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+        return Ok(condition);
+    }
+
     fn or(&mut self) -> Result<Expr, RuntimeError> {
         let mut expr = self.and()?;
         while self.match_token(TokenType::OR) {
@@ -150,10 +593,10 @@ impl Parser {
     }
 
     fn and(&mut self) -> Result<Expr, RuntimeError> {
-        let mut expr = self.equality()?;
+        let mut expr = self.bit_or()?;
         while self.match_token(TokenType::AND) {
             let operator = Parser::previous(self.tokens.clone(), self.current.clone());
-            let right = self.equality()?;
+            let right = self.bit_or()?;
             expr = Expr::Logical(Logical {
                 left: Box::new(expr),
                 operator,
@@ -163,6 +606,49 @@ impl Parser {
         return Ok(expr);
     }
 
+    // the bitwise operators bind looser than equality, as in C
+    fn bit_or(&mut self) -> Result<Expr, RuntimeError> {
+        let mut expr = self.bit_xor()?;
+        while self.match_token(TokenType::PIPE) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let right: Expr = self.bit_xor()?;
+            expr = Expr::Binary(Binary {
+                operator,
+                left: Box::new(expr),
+                right: Box::new(right),
+            })
+        }
+        return Ok(expr);
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, RuntimeError> {
+        let mut expr = self.bit_and()?;
+        while self.match_token(TokenType::CARET) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let right: Expr = self.bit_and()?;
+            expr = Expr::Binary(Binary {
+                operator,
+                left: Box::new(expr),
+                right: Box::new(right),
+            })
+        }
+        return Ok(expr);
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, RuntimeError> {
+        let mut expr = self.equality()?;
+        while self.match_token(TokenType::AMPERSAND) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let right: Expr = self.equality()?;
+            expr = Expr::Binary(Binary {
+                operator,
+                left: Box::new(expr),
+                right: Box::new(right),
+            })
+        }
+        return Ok(expr);
+    }
+
     fn equality(&mut self) -> Result<Expr, RuntimeError> {
         let mut expr = self.comparison()?;
         let types: Vec<TokenType> = std::vec![TokenType::EQUAL_EQUAL, TokenType::BANG_EQUAL];
@@ -179,7 +665,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr, RuntimeError> {
-        let mut expr = self.term()?;
+        let mut expr = self.shift()?;
         let types: Vec<TokenType> = vec![
             TokenType::LESS,
             TokenType::LESS_EQUAL,
@@ -188,7 +674,7 @@ impl Parser {
         ];
         while self.match_tokens(&types) {
             let operator = Parser::previous(self.tokens.clone(), self.current);
-            let right: Expr = self.term()?;
+            let right: Expr = self.shift()?;
             expr = Expr::Binary(Binary {
                 operator: operator.clone(),
                 left: Box::new(expr),
@@ -197,6 +683,21 @@ impl Parser {
         }
         return Ok(expr);
     }
+
+    fn shift(&mut self) -> Result<Expr, RuntimeError> {
+        let mut expr = self.term()?;
+        let types: Vec<TokenType> = vec![TokenType::LESS_LESS, TokenType::GREATER_GREATER];
+        while self.match_tokens(&types) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let right: Expr = self.term()?;
+            expr = Expr::Binary(Binary {
+                operator,
+                left: Box::new(expr),
+                right: Box::new(right),
+            })
+        }
+        return Ok(expr);
+    }
     fn term(&mut self) -> Result<Expr, RuntimeError> {
         let mut expr = self.factor()?;
         let types: Vec<TokenType> = vec![TokenType::MINUS, TokenType::PLUS];
@@ -214,7 +715,7 @@ impl Parser {
 
     fn factor(&mut self) -> Result<Expr, RuntimeError> {
         let mut expr = self.unary()?;
-        let types: Vec<TokenType> = vec![TokenType::STAR, TokenType::SLASH];
+        let types: Vec<TokenType> = vec![TokenType::STAR, TokenType::SLASH, TokenType::PERCENT];
         while self.match_tokens(&types) {
             let operator = Parser::previous(self.tokens.clone(), self.current);
             let right: Expr = self.unary()?;
@@ -228,7 +729,13 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Result<Expr, RuntimeError> {
-        let types: Vec<TokenType> = vec![TokenType::BANG, TokenType::MINUS];
+        let increments: Vec<TokenType> = vec![TokenType::PLUS_PLUS, TokenType::MINUS_MINUS];
+        if self.match_tokens(&increments) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let target = self.unary()?;
+            return Parser::increment(target, operator, false);
+        }
+        let types: Vec<TokenType> = vec![TokenType::BANG, TokenType::MINUS, TokenType::TILDE];
         if self.match_tokens(&types) {
             let operator = Parser::previous(self.tokens.clone(), self.current);
             let right = self.unary()?;
@@ -237,7 +744,137 @@ impl Parser {
                 right: Box::new(right),
             }));
         }
-        return self.primary();
+        return self.power();
+    }
+
+    // right associative and tighter than unary minus, so -2 ** 2 is -(2 ** 2)
+    // while 2 ** -1 and 2 ** 3 ** 2 still parse
+    fn power(&mut self) -> Result<Expr, RuntimeError> {
+        let expr = self.postfix()?;
+        if self.match_token(TokenType::STAR_STAR) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            let right = self.unary()?;
+            return Ok(Expr::Binary(Binary {
+                operator,
+                left: Box::new(expr),
+                right: Box::new(right),
+            }));
+        }
+        return Ok(expr);
+    }
+
+    fn postfix(&mut self) -> Result<Expr, RuntimeError> {
+        let expr = self.call()?;
+        let increments: Vec<TokenType> = vec![TokenType::PLUS_PLUS, TokenType::MINUS_MINUS];
+        if self.match_tokens(&increments) {
+            let operator = Parser::previous(self.tokens.clone(), self.current);
+            return Parser::increment(expr, operator, true);
+        }
+        return Ok(expr);
+    }
+
+    // `++` and `--` add or subtract one like `+= 1` and `-= 1`
+    fn increment(target: Expr, operator: Token, postfix: bool) -> Result<Expr, RuntimeError> {
+        return Ok(Expr::Compound(Compound {
+            target: Box::new(Parser::update_target(target, &operator)?),
+            operator,
+            value: Box::new(Expr::Literal(Literal {
+                value: LiteralType::Int(1),
+            })),
+            postfix,
+        }));
+    }
+
+    // the targets that can be assigned to, as in `assignment`
+    fn update_target(target: Expr, operator: &Token) -> Result<Expr, RuntimeError> {
+        match target {
+            Expr::Variable(_) | Expr::Get(_) | Expr::Index(_) => return Ok(target),
+            _ => {
+                return Err(RuntimeError::error(
+                    operator.line,
+                    "Invalid assignment target. ".to_string(),
+                ))
+            }
+        }
+    }
+
+    fn call(&mut self) -> Result<Expr, RuntimeError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.match_token(TokenType::LEFT_PAREN) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(TokenType::DOT) {
+                let name = self.consume(
+                    TokenType::IDENTIFIER,
+                    "Expect property name after '.'. ".to_string(),
+                )?;
+                expr = Expr::Get(Get {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else if self.match_token(TokenType::LEFT_BRACKET) {
+                expr = self.finish_index(expr)?;
+            } else {
+                break;
+            }
+        }
+        return Ok(expr);
+    }
+
+    // after the '[': either `[index]` or a slice `[start:end]` with optional bounds
+    fn finish_index(&mut self, object: Expr) -> Result<Expr, RuntimeError> {
+        let bracket = Parser::previous(self.tokens.clone(), self.current);
+        let mut start = None;
+        if !self.match_token(TokenType::COLON) {
+            let index = self.expression()?;
+            if !self.match_token(TokenType::COLON) {
+                self.consume(
+                    TokenType::RIGHT_BRACKET,
+                    "Expect ']' after index. ".to_string(),
+                )?;
+                return Ok(Expr::Index(Index {
+                    object: Box::new(object),
+                    bracket,
+                    index: Box::new(index),
+                }));
+            }
+            start = Some(Box::new(index));
+        }
+        let mut end = None;
+        if !self.check(TokenType::RIGHT_BRACKET) {
+            end = Some(Box::new(self.expression()?));
+        }
+        self.consume(
+            TokenType::RIGHT_BRACKET,
+            "Expect ']' after slice. ".to_string(),
+        )?;
+        return Ok(Expr::Slice(Slice {
+            object: Box::new(object),
+            bracket,
+            start,
+            end,
+        }));
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, RuntimeError> {
+        let mut arguments: Vec<Expr> = vec![];
+        if !self.check(TokenType::RIGHT_PAREN) {
+            loop {
+                arguments.push(self.expression()?);
+                if !self.match_token(TokenType::COMMA) {
+                    break;
+                }
+            }
+        }
+        let paren = self.consume(
+            TokenType::RIGHT_PAREN,
+            "Expect ')' after arguments. ".to_string(),
+        )?;
+        return Ok(Expr::Call(Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        }));
     }
 
     fn primary(&mut self) -> Result<Expr, RuntimeError> {
@@ -268,13 +905,65 @@ impl Parser {
                 value: Parser::previous(self.tokens.clone(), self.current).literal,
             }));
         }
+        if self.match_token(TokenType::INTERPOLATION) {
+            return self.interpolation();
+        }
+        if self.match_token(TokenType::FUN) {
+            return self.lambda();
+        }
+        if self.match_token(TokenType::LEFT_BRACKET) {
+            return self.list();
+        }
+        // statements starting with '{' are blocks, so this is only reached in expression position
+        if self.match_token(TokenType::LEFT_BRACE) {
+            return self.map();
+        }
+        if self.check(TokenType::LEFT_PAREN) && self.is_arrow_function() {
+            return self.arrow_function();
+        }
+        if self.match_token(TokenType::SUPER) {
+            let keyword = Parser::previous(self.tokens.clone(), self.current);
+            self.consume(TokenType::DOT, "Expect '.' after 'super'. ".to_string())?;
+            let method = self.consume(
+                TokenType::IDENTIFIER,
+                "Expect superclass method name. ".to_string(),
+            )?;
+            return Ok(Expr::Super(Super {
+                id: self.next_id(),
+                keyword,
+                method,
+            }));
+        }
+        if self.match_token(TokenType::THIS) {
+            return Ok(Expr::This(This {
+                id: self.next_id(),
+                keyword: Parser::previous(self.tokens.clone(), self.current),
+            }));
+        }
         if self.match_token(TokenType::IDENTIFIER) {
             return Ok(Expr::Variable(Variable {
+                id: self.next_id(),
                 name: Parser::previous(self.tokens.clone(), self.current),
             }));
         }
         if self.match_token(TokenType::LEFT_PAREN) {
+            let paren = Parser::previous(self.tokens.clone(), self.current);
             let expr = self.expression()?;
+            // a comma after the first expression makes it a tuple rather than a grouping
+            if self.match_token(TokenType::COMMA) {
+                let mut elements = vec![expr];
+                loop {
+                    elements.push(self.expression()?);
+                    if !self.match_token(TokenType::COMMA) {
+                        break;
+                    }
+                }
+                self.consume(
+                    TokenType::RIGHT_PAREN,
+                    "Expect ')' after tuple elements. ".to_string(),
+                )?;
+                return Ok(Expr::Tuple(Tuple { paren, elements }));
+            }
             self.consume(
                 TokenType::RIGHT_PAREN,
                 String::from("Expect ')' after expression"),
@@ -292,6 +981,68 @@ impl Parser {
         }
     }
 
+    // The scanner splits "a ${b} c" into INTERPOLATION("a "), the tokens of b and
+    // STRING(" c"), with one INTERPOLATION token before every embedded expression.
+    fn interpolation(&mut self) -> Result<Expr, RuntimeError> {
+        let mut parts: Vec<Expr> = vec![];
+        loop {
+            parts.push(Expr::Literal(Literal {
+                value: Parser::previous(self.tokens.clone(), self.current).literal,
+            }));
+            parts.push(self.expression()?);
+            if !self.match_token(TokenType::INTERPOLATION) {
+                break;
+            }
+        }
+        self.consume(
+            TokenType::STRING,
+            "Expect '}' after interpolated expression. ".to_string(),
+        )?;
+        parts.push(Expr::Literal(Literal {
+            value: Parser::previous(self.tokens.clone(), self.current).literal,
+        }));
+        return Ok(Expr::Interpolation(Interpolation { parts }));
+    }
+
+    fn list(&mut self) -> Result<Expr, RuntimeError> {
+        let bracket = Parser::previous(self.tokens.clone(), self.current);
+        let mut elements: Vec<Expr> = vec![];
+        if !self.check(TokenType::RIGHT_BRACKET) {
+            loop {
+                elements.push(self.expression()?);
+                if !self.match_token(TokenType::COMMA) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            TokenType::RIGHT_BRACKET,
+            "Expect ']' after list elements. ".to_string(),
+        )?;
+        return Ok(Expr::List(List { bracket, elements }));
+    }
+
+    fn map(&mut self) -> Result<Expr, RuntimeError> {
+        let brace = Parser::previous(self.tokens.clone(), self.current);
+        let mut entries: Vec<(Expr, Expr)> = vec![];
+        if !self.check(TokenType::RIGHT_BRACE) {
+            loop {
+                let key = self.expression()?;
+                self.consume(TokenType::COLON, "Expect ':' after map key. ".to_string())?;
+                let value = self.expression()?;
+                entries.push((key, value));
+                if !self.match_token(TokenType::COMMA) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            TokenType::RIGHT_BRACE,
+            "Expect '}' after map entries. ".to_string(),
+        )?;
+        return Ok(Expr::Map(Map { brace, entries }));
+    }
+
     fn consume(&mut self, token_type: TokenType, message: String) -> Result<Token, RuntimeError> {
         if self.check(token_type) {
             return Ok(self.advance());
@@ -363,6 +1114,18 @@ impl Parser {
         return self.peek().token_type == token_type;
     }
 
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        return self.next_id;
+    }
+
+    fn check_next(&self, token_type: TokenType) -> bool {
+        if self.is_at_end() {
+            return false;
+        }
+        return self.tokens[self.current + 1].token_type == token_type;
+    }
+
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+
+use crate::scanner::token::Token;
+
+use super::{
+    expression::{self, Expr, ExprVisitor},
+    interpreter::{Interpreter, RuntimeError},
+    statements::{self, Stmt, StmtVisitor},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+// Walks the AST once before execution and tells the interpreter how many
+// scopes away each local variable is bound. Names that are not found in any
+// local scope are left unresolved and looked up in the globals.
+pub struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    // each scope maps a name to whether its initializer has finished resolving
+    scopes: Vec<HashMap<String, bool>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Resolver<'a> {
+        return Resolver {
+            interpreter,
+            scopes: vec![],
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+        };
+    }
+
+    pub fn resolve(&mut self, statements: &Vec<Stmt>) -> Result<(), RuntimeError> {
+        for statement in statements.iter() {
+            self.resolve_stmt(statement)?;
+        }
+        return Ok(());
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) -> Result<(), RuntimeError> {
+        stmt.accept(self)
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) -> Result<(), RuntimeError> {
+        expr.accept(self)
+    }
+
+    fn resolve_function(
+        &mut self,
+        function: &statements::Function,
+        function_type: FunctionType,
+    ) -> Result<(), RuntimeError> {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+        self.begin_scope();
+        for param in function.params.iter() {
+            self.declare(param)?;
+            self.define(param);
+        }
+        let result = self.resolve(&function.body);
+        self.end_scope();
+        self.current_function = enclosing_function;
+        result
+    }
+
+    fn resolve_for(&mut self, stmt: &statements::For) -> Result<(), RuntimeError> {
+        if let Some(initializer) = &stmt.initializer {
+            self.resolve_stmt(initializer)?;
+        }
+        if let Some(condition) = &stmt.condition {
+            self.resolve_expr(condition)?;
+        }
+        if let Some(increment) = &stmt.increment {
+            self.resolve_expr(increment)?;
+        }
+        self.resolve_stmt(&stmt.body)
+    }
+
+    fn resolve_local(&mut self, id: usize, name: &Token) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                self.interpreter.resolve(id, depth);
+                return;
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) -> Result<(), RuntimeError> {
+        if let Some(scope) = self.scopes.last_mut() {
+            if scope.contains_key(&name.lexeme) {
+                return Err(RuntimeError::error(
+                    name.line,
+                    format!("Already a variable named '{}' in this scope.", name.lexeme),
+                ));
+            }
+            scope.insert(name.lexeme.clone(), false);
+        }
+        return Ok(());
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), true);
+        }
+    }
+}
+
+impl<'a> ExprVisitor<()> for Resolver<'a> {
+    fn visit_binary_expr(&mut self, expr: &expression::Binary) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.left)?;
+        self.resolve_expr(&expr.right)
+    }
+
+    fn visit_unary_expr(&mut self, expr: &expression::Unary) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.right)
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &expression::Grouping) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.expression)
+    }
+
+    fn visit_literal_expr(&mut self, _expr: &expression::Literal) -> Result<(), RuntimeError> {
+        return Ok(());
+    }
+
+    fn visit_variable_expr(&mut self, expr: &expression::Variable) -> Result<(), RuntimeError> {
+        if let Some(scope) = self.scopes.last() {
+            if scope.get(&expr.name.lexeme) == Some(&false) {
+                return Err(RuntimeError::error(
+                    expr.name.line,
+                    "Can't read local variable in its own initializer.".to_string(),
+                ));
+            }
+        }
+        self.resolve_local(expr.id, &expr.name);
+        return Ok(());
+    }
+
+    fn visit_assign_expr(&mut self, expr: &expression::Assign) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.value)?;
+        self.resolve_local(expr.id, &expr.name);
+        return Ok(());
+    }
+
+    fn visit_logical_expr(&mut self, expr: &expression::Logical) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.left)?;
+        self.resolve_expr(&expr.right)
+    }
+
+    fn visit_call_expr(&mut self, expr: &expression::Call) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.callee)?;
+        for argument in expr.arguments.iter() {
+            self.resolve_expr(argument)?;
+        }
+        return Ok(());
+    }
+    fn visit_get_expr(&mut self, expr: &expression::Get) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.object)
+    }
+
+    fn visit_set_expr(&mut self, expr: &expression::Set) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.value)?;
+        self.resolve_expr(&expr.object)
+    }
+
+    fn visit_this_expr(&mut self, expr: &expression::This) -> Result<(), RuntimeError> {
+        if self.current_class == ClassType::None {
+            return Err(RuntimeError::error(
+                expr.keyword.line,
+                "Can't use 'this' outside of a class.".to_string(),
+            ));
+        }
+        self.resolve_local(expr.id, &expr.keyword);
+        return Ok(());
+    }
+    fn visit_lambda_expr(&mut self, expr: &expression::Lambda) -> Result<(), RuntimeError> {
+        self.resolve_function(&expr.declaration, FunctionType::Function)
+    }
+
+    fn visit_list_expr(&mut self, expr: &expression::List) -> Result<(), RuntimeError> {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_interpolation_expr(
+        &mut self,
+        expr: &expression::Interpolation,
+    ) -> Result<(), RuntimeError> {
+        for part in expr.parts.iter() {
+            self.resolve_expr(part)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_tuple_expr(&mut self, expr: &expression::Tuple) -> Result<(), RuntimeError> {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_tuple_assign_expr(
+        &mut self,
+        expr: &expression::TupleAssign,
+    ) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.value)?;
+        for target in expr.targets.iter() {
+            self.resolve_local(target.id, &target.name);
+        }
+        return Ok(());
+    }
+
+    fn visit_compound_expr(&mut self, expr: &expression::Compound) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.target)?;
+        self.resolve_expr(&expr.value)
+    }
+
+    fn visit_conditional_expr(
+        &mut self,
+        expr: &expression::Conditional,
+    ) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.condition)?;
+        self.resolve_expr(&expr.then_branch)?;
+        self.resolve_expr(&expr.else_branch)
+    }
+
+    fn visit_map_expr(&mut self, expr: &expression::Map) -> Result<(), RuntimeError> {
+        for (key, value) in expr.entries.iter() {
+            self.resolve_expr(key)?;
+            self.resolve_expr(value)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_index_expr(&mut self, expr: &expression::Index) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.object)?;
+        self.resolve_expr(&expr.index)
+    }
+
+    fn visit_slice_expr(&mut self, expr: &expression::Slice) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.object)?;
+        if let Some(start) = &expr.start {
+            self.resolve_expr(start)?;
+        }
+        if let Some(end) = &expr.end {
+            self.resolve_expr(end)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_set_index_expr(&mut self, expr: &expression::SetIndex) -> Result<(), RuntimeError> {
+        self.resolve_expr(&expr.value)?;
+        self.resolve_expr(&expr.object)?;
+        self.resolve_expr(&expr.index)
+    }
+
+    fn visit_super_expr(&mut self, expr: &expression::Super) -> Result<(), RuntimeError> {
+        match self.current_class {
+            ClassType::None => {
+                return Err(RuntimeError::error(
+                    expr.keyword.line,
+                    "Can't use 'super' outside of a class.".to_string(),
+                ))
+            }
+            ClassType::Class => {
+                return Err(RuntimeError::error(
+                    expr.keyword.line,
+                    "Can't use 'super' in a class with no superclass.".to_string(),
+                ))
+            }
+            ClassType::Subclass => (),
+        }
+        self.resolve_local(expr.id, &expr.keyword);
+        return Ok(());
+    }
+}
+
+impl<'a> StmtVisitor<()> for Resolver<'a> {
+    fn visit_expression_stmt(
+        &mut self,
+        stmt: &statements::ExpressionStmt,
+    ) -> Result<(), RuntimeError> {
+        self.resolve_expr(&stmt.expression)
+    }
+
+    fn visit_print_statment(&mut self, stmt: &statements::PrintStmt) -> Result<(), RuntimeError> {
+        self.resolve_expr(&stmt.expression)
+    }
+
+    fn visit_var_statement(&mut self, stmt: &statements::Var) -> Result<(), RuntimeError> {
+        self.declare(&stmt.name)?;
+        if let Some(initializer) = &stmt.initializer {
+            self.resolve_expr(initializer)?;
+        }
+        self.define(&stmt.name);
+        return Ok(());
+    }
+
+    fn visit_destructure_stmt(
+        &mut self,
+        stmt: &statements::Destructure,
+    ) -> Result<(), RuntimeError> {
+        for name in stmt.names.iter() {
+            self.declare(name)?;
+        }
+        self.resolve_expr(&stmt.initializer)?;
+        for name in stmt.names.iter() {
+            self.define(name);
+        }
+        return Ok(());
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &statements::Block) -> Result<(), RuntimeError> {
+        self.begin_scope();
+        let result = self.resolve(&stmt.statements);
+        self.end_scope();
+        result
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &statements::If) -> Result<(), RuntimeError> {
+        self.resolve_expr(&stmt.condition)?;
+        self.resolve_stmt(&stmt.then_branch)?;
+        if let Some(else_branch) = &stmt.else_branch {
+            self.resolve_stmt(else_branch)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &statements::While) -> Result<(), RuntimeError> {
+        self.resolve_expr(&stmt.condition)?;
+        self.resolve_stmt(&stmt.body)
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &statements::For) -> Result<(), RuntimeError> {
+        // mirrors the scope the interpreter opens for the loop variable
+        self.begin_scope();
+        let result = self.resolve_for(stmt);
+        self.end_scope();
+        result
+    }
+
+    fn visit_break_stmt(&mut self, _stmt: &statements::Break) -> Result<(), RuntimeError> {
+        return Ok(());
+    }
+
+    fn visit_continue_stmt(&mut self, _stmt: &statements::Continue) -> Result<(), RuntimeError> {
+        return Ok(());
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &statements::Function) -> Result<(), RuntimeError> {
+        // defined before the body is resolved so the function can call itself
+        self.declare(&stmt.name)?;
+        self.define(&stmt.name);
+        self.resolve_function(stmt, FunctionType::Function)
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &statements::Return) -> Result<(), RuntimeError> {
+        if let Some(value) = &stmt.value {
+            if self.current_function == FunctionType::Initializer {
+                return Err(RuntimeError::error(
+                    stmt.keyword.line,
+                    "Can't return a value from an initializer.".to_string(),
+                ));
+            }
+            self.resolve_expr(value)?;
+        }
+        return Ok(());
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &statements::Class) -> Result<(), RuntimeError> {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+        self.declare(&stmt.name)?;
+        self.define(&stmt.name);
+
+        if let Some(superclass) = &stmt.superclass {
+            if superclass.name.lexeme == stmt.name.lexeme {
+                return Err(RuntimeError::error(
+                    superclass.name.line,
+                    "A class can't inherit from itself.".to_string(),
+                ));
+            }
+            self.current_class = ClassType::Subclass;
+            self.visit_variable_expr(superclass)?;
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert("super".to_string(), true);
+            }
+        }
+
+        self.begin_scope();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert("this".to_string(), true);
+        }
+        let mut result = Ok(());
+        for method in stmt.methods.iter() {
+            let function_type = if method.name.lexeme == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            result = self.resolve_function(method, function_type);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.end_scope();
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+        self.current_class = enclosing_class;
+        result
+    }
+}
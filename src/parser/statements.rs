@@ -1,16 +1,27 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::scanner::token::{LiteralType, Token};
 
-use super::{expression::Expr, interpreter::RuntimeError};
+use super::{
+    expression::{Expr, Variable},
+    interpreter::RuntimeError,
+};
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
     Expression(ExpressionStmt),
     Print(PrintStmt),
     Var(Var),
+    Destructure(Destructure),
     Block(Block),
     If(If),
+    While(While),
+    For(For),
+    Break(Break),
+    Continue(Continue),
+    Function(Function),
+    Return(Return),
+    Class(Class),
 }
 
 impl Stmt {
@@ -19,8 +30,16 @@ impl Stmt {
             Stmt::Expression(e) => e.accept(visitor),
             Stmt::Print(e) => e.accept(visitor),
             Stmt::Var(v) => v.accept(visitor),
+            Stmt::Destructure(v) => v.accept(visitor),
             Stmt::Block(v) => v.accept(visitor),
             Stmt::If(v) => v.accept(visitor),
+            Stmt::While(v) => v.accept(visitor),
+            Stmt::For(v) => v.accept(visitor),
+            Stmt::Break(v) => v.accept(visitor),
+            Stmt::Continue(v) => v.accept(visitor),
+            Stmt::Function(v) => v.accept(visitor),
+            Stmt::Return(v) => v.accept(visitor),
+            Stmt::Class(v) => v.accept(visitor),
         }
     }
 }
@@ -39,6 +58,14 @@ pub struct Var {
     pub name: Token,
 }
 
+// `var (a, b) = tuple;`
+#[derive(Debug, Clone)]
+pub struct Destructure {
+    pub paren: Token,
+    pub names: Vec<Token>,
+    pub initializer: Expr,
+}
+
 #[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Vec<Stmt>,
@@ -51,12 +78,69 @@ pub struct If {
     pub else_branch: Option<Box<Stmt>>,
 }
 
+#[derive(Debug, Clone)]
+pub struct While {
+    pub condition: Expr,
+    pub body: Box<Stmt>,
+    pub label: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub struct For {
+    pub initializer: Option<Box<Stmt>>,
+    pub condition: Option<Expr>,
+    pub increment: Option<Expr>,
+    pub body: Box<Stmt>,
+    pub label: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Break {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Continue {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: Token,
+    pub params: Vec<Token>,
+    // shared with every function value created from this declaration
+    pub body: Rc<Vec<Stmt>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub name: Token,
+    pub superclass: Option<Variable>,
+    pub methods: Vec<Function>,
+}
+
 pub trait StmtVisitor<T> {
     fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> Result<T, RuntimeError>;
     fn visit_print_statment(&mut self, stmt: &PrintStmt) -> Result<T, RuntimeError>;
     fn visit_var_statement(&mut self, stmt: &Var) -> Result<T, RuntimeError>;
+    fn visit_destructure_stmt(&mut self, stmt: &Destructure) -> Result<T, RuntimeError>;
     fn visit_block_stmt(&mut self, stmt: &Block) -> Result<T, RuntimeError>;
     fn visit_if_stmt(&mut self, stmt: &If) -> Result<T, RuntimeError>;
+    fn visit_while_stmt(&mut self, stmt: &While) -> Result<T, RuntimeError>;
+    fn visit_for_stmt(&mut self, stmt: &For) -> Result<T, RuntimeError>;
+    fn visit_break_stmt(&mut self, stmt: &Break) -> Result<T, RuntimeError>;
+    fn visit_continue_stmt(&mut self, stmt: &Continue) -> Result<T, RuntimeError>;
+    fn visit_function_stmt(&mut self, stmt: &Function) -> Result<T, RuntimeError>;
+    fn visit_return_stmt(&mut self, stmt: &Return) -> Result<T, RuntimeError>;
+    fn visit_class_stmt(&mut self, stmt: &Class) -> Result<T, RuntimeError>;
 }
 
 impl ExpressionStmt {
@@ -76,6 +160,11 @@ impl Var {
         return visitor.visit_var_statement(self);
     }
 }
+impl Destructure {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_destructure_stmt(self);
+    }
+}
 impl Block {
     pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
         return visitor.visit_block_stmt(self);
@@ -87,22 +176,64 @@ impl If {
         return visitor.visit_if_stmt(self);
     }
 }
+
+impl While {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_while_stmt(self);
+    }
+}
+
+impl For {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_for_stmt(self);
+    }
+}
+
+impl Break {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_break_stmt(self);
+    }
+}
+
+impl Continue {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_continue_stmt(self);
+    }
+}
+
+impl Function {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_function_stmt(self);
+    }
+}
+
+impl Return {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_return_stmt(self);
+    }
+}
+
+impl Class {
+    pub fn accept<T>(&self, visitor: &mut dyn StmtVisitor<T>) -> Result<T, RuntimeError> {
+        return visitor.visit_class_stmt(self);
+    }
+}
 #[derive(Debug, Clone)]
 pub struct Environment {
-    pub enclosing: Option<Box<Environment>>,
+    pub enclosing: Option<Rc<RefCell<Environment>>>,
     pub values: HashMap<String, LiteralType>,
 }
 
-impl<'a> Environment {
+impl Environment {
     pub fn new() -> Environment {
         return Environment {
             enclosing: None,
             values: HashMap::new(),
         };
     }
-    pub fn new_with_enclosing(enclosing: Environment) -> Environment {
+    pub fn new_with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
         return Environment {
-            enclosing: Some(Box::new(enclosing)),
+            enclosing: Some(enclosing),
             values: HashMap::new(),
         };
     }
@@ -114,7 +245,7 @@ impl<'a> Environment {
         match self.values.get(&name.lexeme) {
             None => {
                 if let Some(e) = &self.enclosing {
-                    return e.get(name);
+                    return e.borrow().get(name);
                 }
                 return Err(RuntimeError::error(
                     name.line,
@@ -124,12 +255,55 @@ impl<'a> Environment {
             Some(v) => return Ok(v.clone()),
         }
     }
+    pub fn ancestor(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+    ) -> Rc<RefCell<Environment>> {
+        let mut current = environment.clone();
+        for _ in 0..distance {
+            let enclosing = current
+                .borrow()
+                .enclosing
+                .clone()
+                .expect("resolved scope depth is deeper than the environment chain");
+            current = enclosing;
+        }
+        return current;
+    }
+    pub fn get_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: Token,
+    ) -> Result<LiteralType, RuntimeError> {
+        let ancestor = Environment::ancestor(environment, distance);
+        let value = ancestor.borrow().values.get(&name.lexeme).cloned();
+        match value {
+            Some(v) => return Ok(v),
+            None => {
+                return Err(RuntimeError::error(
+                    name.line,
+                    String::from("Undefined variable '".to_string() + &name.lexeme + "'."),
+                ))
+            }
+        }
+    }
+    pub fn assign_at(
+        environment: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: Token,
+        value: LiteralType,
+    ) {
+        Environment::ancestor(environment, distance)
+            .borrow_mut()
+            .values
+            .insert(name.lexeme, value);
+    }
     pub fn assign(&mut self, name: Token, value: LiteralType) -> Result<(), RuntimeError> {
         if self.values.contains_key(&name.lexeme) {
             self.values.insert(name.lexeme.clone(), value);
             return Ok(());
-        } else if let Some(e) = &mut self.enclosing {
-            e.assign(name.clone(), value)?;
+        } else if let Some(e) = &self.enclosing {
+            return e.borrow_mut().assign(name, value);
         }
         return Err(RuntimeError::error(
             name.line,
@@ -1,32 +1,45 @@
-use crate::report_error;
+use crate::{parser::bigint::BigInt, report_error};
 
 use super::token::{LiteralType, Token, TokenType, KEYWORDS};
 pub struct Scanner {
     pub source: String,
+    // `start` and `current` count chars, so this is the length in chars rather than bytes
+    length: usize,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: i32,
+    // one entry per `${` still open, counting the `{` opened inside it
+    interpolations: Vec<usize>,
     pub error: bool,
 }
 
 pub fn new(source: String) -> Scanner {
     Scanner {
+        length: source.chars().count(),
         source,
         tokens: Vec::new(), // Initialize the tokens vector
         start: 0,
         current: 0,
         line: 1,
+        interpolations: Vec::new(),
         error: false,
     }
 }
 
 impl Scanner {
     pub fn scan_tokens(&mut self) -> &Vec<Token> {
-        while self.current < self.source.len() {
+        while !self.is_at_end() {
             self.start = self.current;
             self.scan_token();
         }
+        if !self.interpolations.is_empty() {
+            report_error(
+                self.line,
+                &String::from("Unterminated string interpolation"),
+            );
+            self.error = true;
+        }
         self.tokens.push(Token {
             token_type: super::token::TokenType::EOF,
             lexeme: String::from(""),
@@ -48,14 +61,88 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFT_PAREN, None),
             ')' => self.add_token(TokenType::RIGHT_PAREN, None),
-            '{' => self.add_token(TokenType::LEFT_BRACE, None),
-            '}' => self.add_token(TokenType::RIGHT_BRACE, None),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LEFT_BRACE, None);
+            }
+            '}' => match self.interpolations.last_mut() {
+                // closes the `${`, so the rest of the string follows
+                Some(0) => {
+                    self.interpolations.pop();
+                    if let Some(Token {
+                        token_type: TokenType::INTERPOLATION,
+                        ..
+                    }) = self.tokens.last()
+                    {
+                        report_error(self.line, &String::from("Empty string interpolation"));
+                        self.error = true;
+                    }
+                    self.string_literal();
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RIGHT_BRACE, None);
+                }
+                None => self.add_token(TokenType::RIGHT_BRACE, None),
+            },
+            '[' => self.add_token(TokenType::LEFT_BRACKET, None),
+            ']' => self.add_token(TokenType::RIGHT_BRACKET, None),
             ',' => self.add_token(TokenType::COMMA, None),
+            ':' => self.add_token(TokenType::COLON, None),
+            '?' => self.add_token(TokenType::QUESTION, None),
             '.' => self.add_token(TokenType::DOT, None),
-            '-' => self.add_token(TokenType::MINUS, None),
-            '+' => self.add_token(TokenType::PLUS, None),
+            '-' => {
+                if self.match_char('-') {
+                    token_type = TokenType::MINUS_MINUS;
+                    self.current += 1
+                } else if self.match_char('=') {
+                    token_type = TokenType::MINUS_EQUAL;
+                    self.current += 1
+                } else {
+                    token_type = TokenType::MINUS
+                };
+                self.add_token(token_type, None);
+            }
+            '+' => {
+                if self.match_char('+') {
+                    token_type = TokenType::PLUS_PLUS;
+                    self.current += 1
+                } else if self.match_char('=') {
+                    token_type = TokenType::PLUS_EQUAL;
+                    self.current += 1
+                } else {
+                    token_type = TokenType::PLUS
+                };
+                self.add_token(token_type, None);
+            }
             ';' => self.add_token(TokenType::SEMICOLON, None),
-            '*' => self.add_token(TokenType::STAR, None),
+            '&' => self.add_token(TokenType::AMPERSAND, None),
+            '|' => self.add_token(TokenType::PIPE, None),
+            '^' => self.add_token(TokenType::CARET, None),
+            '~' => self.add_token(TokenType::TILDE, None),
+            '%' => {
+                if self.match_char('=') {
+                    token_type = TokenType::PERCENT_EQUAL;
+                    self.current += 1
+                } else {
+                    token_type = TokenType::PERCENT
+                };
+                self.add_token(token_type, None);
+            }
+            '*' => {
+                if self.match_char('*') {
+                    token_type = TokenType::STAR_STAR;
+                    self.current += 1
+                } else if self.match_char('=') {
+                    token_type = TokenType::STAR_EQUAL;
+                    self.current += 1
+                } else {
+                    token_type = TokenType::STAR
+                };
+                self.add_token(token_type, None);
+            }
             '!' => {
                 if self.match_char('=') {
                     token_type = TokenType::BANG_EQUAL;
@@ -69,6 +156,9 @@ impl Scanner {
                 if self.match_char('=') {
                     token_type = TokenType::EQUAL_EQUAL;
                     self.current += 1
+                } else if self.match_char('>') {
+                    token_type = TokenType::ARROW;
+                    self.current += 1
                 } else {
                     token_type = TokenType::EQUAL
                 };
@@ -78,6 +168,9 @@ impl Scanner {
                 if self.match_char('=') {
                     token_type = TokenType::LESS_EQUAL;
                     self.current += 1
+                } else if self.match_char('<') {
+                    token_type = TokenType::LESS_LESS;
+                    self.current += 1
                 } else {
                     token_type = TokenType::LESS;
                 };
@@ -87,6 +180,9 @@ impl Scanner {
                 if self.match_char('=') {
                     token_type = TokenType::GREATER_EQUAL;
                     self.current += 1
+                } else if self.match_char('>') {
+                    token_type = TokenType::GREATER_GREATER;
+                    self.current += 1
                 } else {
                     token_type = TokenType::GREATER;
                 };
@@ -97,16 +193,32 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.current += 1
                     }
+                } else if self.match_char('*') {
+                    self.current += 1;
+                    self.block_comment();
+                } else if self.match_char('=') {
+                    self.current += 1;
+                    self.add_token(TokenType::SLASH_EQUAL, None);
                 } else {
                     self.add_token(TokenType::SLASH, None);
                 }
             }
             '\n' => self.line += 1,
             ' ' | '\r' | '\t' => (),
-            '"' => self.string_literal(),
+            '"' => {
+                if self.peek() == '"' && self.peek_next() == '"' {
+                    self.current += 2;
+                    self.raw_string(3);
+                } else {
+                    self.string_literal();
+                }
+            }
             _ => {
                 if is_digit(c) {
                     self.number();
+                } else if c == 'r' && self.peek() == '"' {
+                    self.current += 1;
+                    self.raw_string(1);
                 } else if is_alpha(c) {
                     self.identifier();
                 } else {
@@ -158,7 +270,7 @@ impl Scanner {
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
+        if self.current + 1 >= self.length {
             return '\0';
         }
         return self
@@ -169,40 +281,255 @@ impl Scanner {
     }
 
     fn is_at_end(&self) -> bool {
-        return self.current >= self.source.len();
+        return self.current >= self.length;
     }
 
+    // `/* ... */`, where comments may nest
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                report_error(self.line, &String::from("Unterminated block comment"));
+                self.error = true;
+                return;
+            }
+            let c = self.peek();
+            if c == '/' && self.peek_next() == '*' {
+                depth += 1;
+                self.current += 2;
+            } else if c == '*' && self.peek_next() == '/' {
+                depth -= 1;
+                self.current += 2;
+            } else {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                self.current += 1;
+            }
+        }
+    }
+
+    // r"..." and """...""" keep everything up to the closing quotes as is,
+    // with no escapes or interpolation
+    fn raw_string(&mut self, quotes: usize) {
+        let opening = self.current;
+        while !self.closing_quotes(quotes) {
+            if self.is_at_end() {
+                report_error(self.line, &String::from("Unterminated string literal"));
+                self.error = true;
+                return;
+            }
+            if self.peek() == '\n' {
+                self.line += 1;
+            }
+            self.current += 1;
+        }
+        let value = get_substr(&self.source, opening, self.current - opening);
+        self.current += quotes;
+        self.add_token(TokenType::STRING, Some(LiteralType::String(value)));
+    }
+
+    fn closing_quotes(&self, quotes: usize) -> bool {
+        return get_substr(&self.source, self.current, quotes) == "\"".repeat(quotes);
+    }
+
+    // Also scans the rest of an interpolated string after its `}`. The value is
+    // built as we go because escape sequences don't map one to one onto the source.
     fn string_literal(&mut self) {
+        let mut value = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
+            let c = self.peek();
+            if c == '$' && self.peek_next() == '{' {
+                self.current += 2;
+                self.add_token(TokenType::INTERPOLATION, Some(LiteralType::String(value)));
+                self.interpolations.push(0);
+                return;
+            }
+            if c == '\n' {
                 self.line += 1;
             }
             self.current += 1;
+            if c == '\\' {
+                if let Some(escaped) = self.escape_sequence() {
+                    value.push(escaped);
+                }
+            } else {
+                value.push(c);
+            }
         }
         if self.is_at_end() {
             report_error(self.line, &String::from("Unterminated string literal"));
+            self.error = true;
             return;
         }
         self.current += 1;
 
-        let value = get_substr(&self.source, self.start + 1, self.current - self.start - 2);
         self.add_token(TokenType::STRING, Some(LiteralType::String(value)));
     }
 
+    // called after the backslash; reports and skips anything it doesn't know
+    fn escape_sequence(&mut self) -> Option<char> {
+        if self.is_at_end() {
+            return None;
+        }
+        let c = self.peek();
+        self.current += 1;
+        match c {
+            'n' => return Some('\n'),
+            't' => return Some('\t'),
+            'r' => return Some('\r'),
+            '0' => return Some('\0'),
+            '\\' => return Some('\\'),
+            '"' => return Some('"'),
+            '$' => return Some('$'),
+            'u' => return self.unicode_escape(),
+            _ => {
+                if c == '\n' {
+                    self.line += 1;
+                }
+                report_error(self.line, &format!("Unknown escape sequence '\\{}'", c));
+                self.error = true;
+                return None;
+            }
+        }
+    }
+
+    // `\u{...}` with one to six hex digits naming a Unicode scalar value
+    fn unicode_escape(&mut self) -> Option<char> {
+        if self.peek() != '{' {
+            report_error(self.line, &String::from("Expect '{' after '\\u'"));
+            self.error = true;
+            return None;
+        }
+        self.current += 1;
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.peek());
+            self.current += 1;
+        }
+        if self.peek() != '}' || digits.is_empty() || digits.len() > 6 {
+            report_error(self.line, &String::from("Invalid unicode escape sequence"));
+            self.error = true;
+            return None;
+        }
+        self.current += 1;
+        let escaped = u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32);
+        if escaped.is_none() {
+            report_error(
+                self.line,
+                &format!("Invalid unicode scalar value '{}'", digits),
+            );
+            self.error = true;
+        }
+        return escaped;
+    }
+
     fn number(&mut self) {
-        while is_digit(self.peek()) {
+        let radix = match (
+            get_substr(&self.source, self.start, 1).as_str(),
+            self.peek(),
+        ) {
+            ("0", 'x' | 'X') => 16,
+            ("0", 'o' | 'O') => 8,
+            ("0", 'b' | 'B') => 2,
+            _ => 10,
+        };
+        if radix != 10 {
             self.current += 1;
+            self.radix_number(radix);
+            return;
         }
+        self.digits();
         if self.peek() == '.' && is_digit(self.peek_next()) {
             self.current += 1;
-
-            while is_digit(self.peek()) {
+            self.digits();
+        }
+        if self.peek() == 'e' || self.peek() == 'E' {
+            self.current += 1;
+            if self.peek() == '+' || self.peek() == '-' {
                 self.current += 1;
             }
+            if !is_digit(self.peek()) {
+                self.number_error(String::from("Expect digits in exponent"));
+                return;
+            }
+            self.digits();
+        }
+        let text = get_substr(&self.source, self.start, self.current - self.start);
+        if !separators_between_digits(&text, 10) {
+            self.number_error(String::from("Digit separators must be between digits"));
+            return;
+        }
+        let digits = text.replace('_', "");
+        if digits.contains(['.', 'e', 'E']) {
+            match digits.parse::<f64>() {
+                Ok(value) => self.add_token(TokenType::NUMBER, Some(LiteralType::Float(value))),
+                Err(_) => self.number_error(format!("Invalid number literal '{}'", text)),
+            }
+            return;
+        }
+        // an `n` suffix asks for a big integer
+        let big = self.peek() == 'n' && !is_alpha_numeric(self.peek_next());
+        if big {
+            self.current += 1;
+        }
+        self.integer(&digits, 10, big);
+    }
+
+    // `0x`, `0o` or `0b` followed by digits in that base
+    fn radix_number(&mut self, radix: u32) {
+        while is_alpha_numeric(self.peek()) {
+            self.current += 1;
+        }
+        let text = get_substr(&self.source, self.start, self.current - self.start);
+        let mut digits = &text[2..];
+        let big = digits.ends_with('n');
+        if big {
+            digits = &digits[..digits.len() - 1];
+        }
+        if digits.is_empty() {
+            self.number_error(format!("Expect digits after '{}'", &text[..2]));
+            return;
+        }
+        if let Some(c) = digits.chars().find(|c| *c != '_' && !c.is_digit(radix)) {
+            self.number_error(format!(
+                "Invalid digit '{}' in number literal '{}'",
+                c, text
+            ));
+            return;
+        }
+        if !separators_between_digits(digits, radix) {
+            self.number_error(String::from("Digit separators must be between digits"));
+            return;
+        }
+        self.integer(&digits.replace('_', ""), radix, big);
+    }
+
+    // integer literals too large for an i64 become big integers
+    fn integer(&mut self, digits: &str, radix: u32, big: bool) {
+        if !big {
+            if let Ok(value) = i64::from_str_radix(digits, radix) {
+                self.add_token(TokenType::NUMBER, Some(LiteralType::Int(value)));
+                return;
+            }
+        }
+        match BigInt::parse_radix(digits, radix) {
+            Some(value) => self.add_token(TokenType::NUMBER, Some(LiteralType::BigInt(value))),
+            None => self.number_error(format!("Invalid number literal '{}'", digits)),
         }
-        let substr: String = get_substr(&self.source, self.start, self.current - self.start);
-        let value: f32 = substr.parse().expect("error when parsing number");
-        self.add_token(TokenType::NUMBER, Some(LiteralType::Float(value)));
+    }
+
+    fn digits(&mut self) {
+        while is_digit(self.peek()) || self.peek() == '_' {
+            self.current += 1;
+        }
+    }
+
+    fn number_error(&mut self, message: String) {
+        report_error(self.line, &message);
+        self.error = true;
     }
 
     fn identifier(&mut self) {
@@ -222,6 +549,22 @@ fn get_substr(str: &String, start: usize, size: usize) -> String {
     return str.chars().skip(start).take(size).collect();
 }
 
+// `_` may only separate two digits, as in 1_000_000
+fn separators_between_digits(text: &str, radix: u32) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_'
+            && (i == 0
+                || i + 1 == chars.len()
+                || !chars[i - 1].is_digit(radix)
+                || !chars[i + 1].is_digit(radix))
+        {
+            return false;
+        }
+    }
+    return true;
+}
+
 fn is_digit(c: char) -> bool {
     if c >= '0' && c <= '9' {
         return true;
@@ -236,3 +579,132 @@ fn is_alpha(c: char) -> bool {
 fn is_alpha_numeric(c: char) -> bool {
     return is_alpha(c) || is_digit(c);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::bigint::BigInt;
+
+    use super::{
+        super::token::{LiteralType, Token, TokenType},
+        new,
+    };
+
+    // scans `source` and returns its tokens along with the scanner's error flag
+    fn scan(source: &str) -> (Vec<Token>, bool) {
+        let mut scanner = new(source.to_string());
+        let tokens = scanner.scan_tokens().clone();
+        return (tokens, scanner.error);
+    }
+
+    fn string(value: &str) -> LiteralType {
+        return LiteralType::String(value.to_string());
+    }
+
+    #[test]
+    fn escape_sequences_are_decoded() {
+        let (tokens, error) = scan(r#""a\nb\t\\\"\r\0\u{1F600}\u{e9}\${x}""#);
+        assert!(!error);
+        assert_eq!(tokens[0].token_type, TokenType::STRING);
+        assert_eq!(
+            tokens[0].literal,
+            string("a\nb\t\\\"\r\0\u{1F600}\u{e9}${x}")
+        );
+        assert_eq!(tokens[1].token_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn unknown_escape_is_a_scan_error() {
+        let (tokens, error) = scan("\n\n\"bad \\q\" x");
+        assert!(error);
+        assert_eq!(tokens[0].token_type, TokenType::STRING);
+        assert_eq!(tokens[0].line, 3);
+        assert_eq!(tokens[1].lexeme, "x");
+        assert_eq!(tokens[1].line, 3);
+    }
+
+    #[test]
+    fn malformed_unicode_escapes_are_scan_errors() {
+        assert!(scan(r#""\u41""#).1);
+        assert!(scan(r#""\u{110000}""#).1);
+        assert!(scan(r#""\u{D800}""#).1);
+        assert!(scan(r#""\u{41""#).1);
+        assert!(!scan(r#""\u{41}""#).1);
+    }
+
+    #[test]
+    fn block_comments_nest_and_count_lines() {
+        let (tokens, error) = scan("a /* one\n/* two\n */ still\n*/ b");
+        assert!(!error);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!((tokens[0].lexeme.as_str(), tokens[0].line), ("a", 1));
+        assert_eq!((tokens[1].lexeme.as_str(), tokens[1].line), ("b", 4));
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_a_scan_error() {
+        let (tokens, error) = scan("a /* /* */\n");
+        assert!(error);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].token_type, TokenType::EOF);
+        assert_eq!(tokens[1].line, 2);
+    }
+
+    #[test]
+    fn raw_strings_keep_backslashes_and_newlines() {
+        let (tokens, error) = scan("r\"C:\\path\\n\nz\" d");
+        assert!(!error);
+        assert_eq!(tokens[0].literal, string("C:\\path\\n\nz"));
+        assert_eq!((tokens[1].lexeme.as_str(), tokens[1].line), ("d", 2));
+    }
+
+    #[test]
+    fn triple_quoted_strings_keep_quotes_and_newlines() {
+        let (tokens, error) = scan("\"\"\"say \"hi\"\n\\n\"\"\" c");
+        assert!(!error);
+        assert_eq!(tokens[0].literal, string("say \"hi\"\n\\n"));
+        assert_eq!((tokens[1].lexeme.as_str(), tokens[1].line), ("c", 2));
+    }
+
+    #[test]
+    fn unterminated_raw_strings_are_scan_errors() {
+        let (tokens, error) = scan("r\"abc\nx");
+        assert!(error);
+        assert_eq!(tokens[0].token_type, TokenType::EOF);
+        assert_eq!(tokens[0].line, 2);
+        assert!(scan("\"\"\"abc\"\"").1);
+    }
+
+    #[test]
+    fn number_literals_in_every_radix() {
+        let (tokens, error) = scan("0xFF 0b1010 0o755 1e-9 6.02E23 1_000_000 1.5");
+        assert!(!error);
+        let literals: Vec<String> = tokens[..7]
+            .iter()
+            .map(|t| format!("{:?}", t.literal))
+            .collect();
+        assert_eq!(
+            literals,
+            ["255", "10", "493", "1e-9", "6.02e23", "1000000", "1.5"]
+        );
+        assert!(matches!(tokens[0].literal, LiteralType::Int(255)));
+    }
+
+    #[test]
+    fn oversized_literals_become_big_integers() {
+        let (tokens, error) = scan("0xFFFF_FFFF_FFFF_FFFF_F 12n");
+        assert!(!error);
+        let expected = BigInt::parse("295147905179352825855").unwrap();
+        assert!(matches!(&tokens[0].literal, LiteralType::BigInt(b) if *b == expected));
+        assert!(matches!(&tokens[1].literal, LiteralType::BigInt(b) if *b == BigInt::from_i64(12)));
+    }
+
+    #[test]
+    fn malformed_number_literals_are_scan_errors() {
+        for source in ["0x", "0b12", "0o8", "1e", "1e+", "1__0", "1_", "0x_1"] {
+            let (tokens, error) = scan(&format!("\n{} x", source));
+            assert!(error, "{} should not scan", source);
+            let last = &tokens[tokens.len() - 2];
+            assert_eq!((last.lexeme.as_str(), last.line), ("x", 2), "{}", source);
+        }
+    }
+}
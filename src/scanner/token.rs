@@ -1,7 +1,21 @@
 use lazy_static::lazy_static;
-use std::{cmp::Ordering, collections::HashMap, fmt, ops::Add};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub},
+    rc::Rc,
+};
 
-use crate::parser::interpreter::RuntimeError;
+use crate::parser::{
+    bigint::BigInt,
+    callable::Function,
+    class::{Class, Instance},
+    interpreter::RuntimeError,
+    map::Map,
+    natives::NativeFunction,
+};
 
 #[derive(Debug, Clone)]
 pub struct Token {
@@ -20,29 +34,53 @@ pub enum TokenType {
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
     COMMA,
+    COLON,
+    QUESTION,
     DOT,
     MINUS,
     PLUS,
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
     // One or two character tokens.
+    PLUS_PLUS,
+    MINUS_MINUS,
+    PLUS_EQUAL,
+    MINUS_EQUAL,
+    STAR_EQUAL,
+    SLASH_EQUAL,
+    PERCENT_EQUAL,
     BANG,
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    STAR_STAR,
+    ARROW,
     GREATER,
     GREATER_EQUAL,
     LESS,
     LESS_EQUAL,
+    LESS_LESS,
+    GREATER_GREATER,
     // Literals.
     IDENTIFIER,
     STRING,
+    // the part of a string literal before an embedded `${`
+    INTERPOLATION,
     NUMBER,
     // Keywords.
     AND,
+    BREAK,
     CLASS,
+    CONTINUE,
     ELSE,
     FALSE,
     FUN,
@@ -62,8 +100,17 @@ pub enum TokenType {
 #[derive(Clone)]
 pub enum LiteralType {
     String(String),
-    Float(f32),
+    Int(i64),
+    BigInt(BigInt),
+    Float(f64),
     Bool(bool),
+    Function(Rc<Function>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<LiteralType>>>),
+    Tuple(Rc<Vec<LiteralType>>),
+    Map(Rc<RefCell<Map>>),
     Null,
 }
 
@@ -71,21 +118,197 @@ impl Add for LiteralType {
     type Output = Result<LiteralType, String>;
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (LiteralType::Float(x), LiteralType::Float(y)) => return Ok(LiteralType::Float(x + y)),
             (LiteralType::String(x), LiteralType::String(y)) => {
                 return Ok(LiteralType::String(format!("{}{}", x, y)))
             }
+            (x, y) if x.is_number() && y.is_number() => {
+                return x.arithmetic(y, i64::checked_add, |a, b| Ok(a.add(b)), |a, b| a + b)
+            }
             _ => return Err(String::from("Operands must be two numbers or two strings")),
         }
     }
 }
 
+impl Sub for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        return self.arithmetic(rhs, i64::checked_sub, |a, b| Ok(a.sub(b)), |a, b| a - b);
+    }
+}
+
+impl Mul for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        return self.arithmetic(rhs, i64::checked_mul, |a, b| Ok(a.mul(b)), |a, b| a * b);
+    }
+}
+
+// Dividing two integers rounds towards negative infinity (floored, as in Python),
+// so `-7 / 2` is -4 and `a == (a / b) * b + a % b` holds for every sign.
+impl Div for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn div(self, rhs: Self) -> Self::Output {
+        return self.arithmetic(
+            rhs,
+            |a, b| {
+                let q = a.checked_div(b)?;
+                if a % b != 0 && (a < 0) != (b < 0) {
+                    return Some(q - 1);
+                }
+                return Some(q);
+            },
+            |a, b| match a.div_rem(b) {
+                Some((q, r)) => {
+                    if !r.is_zero() && r.is_negative() != b.is_negative() {
+                        return Ok(q.sub(&BigInt::from_i64(1)));
+                    }
+                    return Ok(q);
+                }
+                None => return Err(String::from("Division by zero.")),
+            },
+            |a, b| a / b,
+        );
+    }
+}
+
+// The remainder takes the sign of the divisor (floored modulo, as in Python),
+// so `-7 % 3` is 2 and `n % m` always lands in `0..m` for a positive `m`.
+impl Rem for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn rem(self, rhs: Self) -> Self::Output {
+        return self.arithmetic(
+            rhs,
+            |a, b| {
+                let r = a.checked_rem(b)?;
+                if r != 0 && (r < 0) != (b < 0) {
+                    return r.checked_add(b);
+                }
+                return Some(r);
+            },
+            |a, b| match a.div_rem(b) {
+                Some((_, r)) => {
+                    if !r.is_zero() && r.is_negative() != b.is_negative() {
+                        return Ok(r.add(b));
+                    }
+                    return Ok(r);
+                }
+                None => return Err(String::from("Division by zero.")),
+            },
+            |a, b| {
+                let r = a % b;
+                if r != 0.0 && (r < 0.0) != (b < 0.0) {
+                    return r + b;
+                }
+                return r;
+            },
+        );
+    }
+}
+
+impl Neg for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn neg(self) -> Self::Output {
+        match self {
+            LiteralType::Int(x) => match x.checked_neg() {
+                Some(n) => return Ok(LiteralType::Int(n)),
+                None => return Ok(LiteralType::BigInt(BigInt::from_i64(x).negated())),
+            },
+            LiteralType::BigInt(x) => return Ok(LiteralType::BigInt(x.negated())),
+            LiteralType::Float(x) => return Ok(LiteralType::Float(-x)),
+            _ => return Err(String::from("Operand must be a number")),
+        }
+    }
+}
+
+impl BitAnd for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        return self.bitwise(rhs, |a, b| a & b, |a, b| a & b);
+    }
+}
+
+impl BitOr for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        return self.bitwise(rhs, |a, b| a | b, |a, b| a | b);
+    }
+}
+
+impl BitXor for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        return self.bitwise(rhs, |a, b| a ^ b, |a, b| a ^ b);
+    }
+}
+
+// `~`, the bitwise complement; `!` is logical not and never reaches here
+impl Not for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn not(self) -> Self::Output {
+        match self {
+            LiteralType::Int(x) => return Ok(LiteralType::Int(!x)),
+            LiteralType::BigInt(x) => {
+                return Ok(LiteralType::BigInt(x.negated().sub(&BigInt::from_i64(1))))
+            }
+            _ => return Err(String::from("Operand of '~' must be an integer.")),
+        }
+    }
+}
+
+// shifting left grows into a big integer instead of dropping bits
+impl Shl for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn shl(self, rhs: Self) -> Self::Output {
+        let bits = LiteralType::shift_amount(&self, &rhs)?;
+        if let LiteralType::Int(x) = self {
+            if bits < 64 && (x << bits) >> bits == x {
+                return Ok(LiteralType::Int(x << bits));
+            }
+        }
+        let x = self.as_big_int().unwrap_or(BigInt::from_i64(0));
+        return Ok(LiteralType::BigInt(x.shl(bits)));
+    }
+}
+
+// an arithmetic shift, so the sign is kept
+impl Shr for LiteralType {
+    type Output = Result<LiteralType, String>;
+    fn shr(self, rhs: Self) -> Self::Output {
+        let bits = LiteralType::shift_amount(&self, &rhs)?;
+        match self {
+            LiteralType::Int(x) => return Ok(LiteralType::Int(x >> bits.min(63))),
+            LiteralType::BigInt(x) => return Ok(LiteralType::BigInt(x.shr(bits))),
+            _ => return Err(String::from("Bitwise operands must be integers.")),
+        }
+    }
+}
+
 impl PartialEq for LiteralType {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (LiteralType::String(x), LiteralType::String(y)) => x == y,
+            (LiteralType::Int(x), LiteralType::Int(y)) => x == y,
             (LiteralType::Float(x), LiteralType::Float(y)) => x == y,
+            (LiteralType::Int(x), LiteralType::Float(y))
+            | (LiteralType::Float(y), LiteralType::Int(x)) => {
+                compare_integer_float(&BigInt::from_i64(*x), *y) == Some(Ordering::Equal)
+            }
+            (LiteralType::BigInt(x), LiteralType::BigInt(y)) => x == y,
+            (LiteralType::BigInt(x), LiteralType::Int(y))
+            | (LiteralType::Int(y), LiteralType::BigInt(x)) => *x == BigInt::from_i64(*y),
+            (LiteralType::BigInt(x), LiteralType::Float(y))
+            | (LiteralType::Float(y), LiteralType::BigInt(x)) => {
+                compare_integer_float(x, *y) == Some(Ordering::Equal)
+            }
             (LiteralType::Bool(x), LiteralType::Bool(y)) => x == y,
+            (LiteralType::Function(x), LiteralType::Function(y)) => Rc::ptr_eq(x, y),
+            (LiteralType::Native(x), LiteralType::Native(y)) => Rc::ptr_eq(x, y),
+            (LiteralType::Class(x), LiteralType::Class(y)) => Rc::ptr_eq(x, y),
+            (LiteralType::Instance(x), LiteralType::Instance(y)) => Rc::ptr_eq(x, y),
+            (LiteralType::List(x), LiteralType::List(y)) => Rc::ptr_eq(x, y),
+            // tuples can't change, so they compare by value
+            (LiteralType::Tuple(x), LiteralType::Tuple(y)) => x == y,
+            (LiteralType::Map(x), LiteralType::Map(y)) => Rc::ptr_eq(x, y),
             (LiteralType::Null, LiteralType::Null) => return true,
             _ => false,
         }
@@ -102,7 +325,22 @@ impl PartialOrd for LiteralType {
                     return Some(Ordering::Equal);
                 }
             }
+            (LiteralType::Int(x), LiteralType::Int(y)) => return x.partial_cmp(y),
             (LiteralType::Float(x), LiteralType::Float(y)) => return x.partial_cmp(y),
+            (LiteralType::Int(x), LiteralType::Float(y)) => {
+                return compare_integer_float(&BigInt::from_i64(*x), *y)
+            }
+            (LiteralType::Float(x), LiteralType::Int(y)) => {
+                return compare_integer_float(&BigInt::from_i64(*y), *x).map(Ordering::reverse)
+            }
+            (LiteralType::BigInt(_), LiteralType::Int(_) | LiteralType::BigInt(_))
+            | (LiteralType::Int(_), LiteralType::BigInt(_)) => {
+                return self.as_big_int()?.partial_cmp(&other.as_big_int()?)
+            }
+            (LiteralType::BigInt(x), LiteralType::Float(y)) => return compare_integer_float(x, *y),
+            (LiteralType::Float(x), LiteralType::BigInt(y)) => {
+                return compare_integer_float(y, *x).map(Ordering::reverse)
+            }
             (LiteralType::String(x), LiteralType::String(y)) => return x.partial_cmp(y),
             (LiteralType::Bool(x), LiteralType::Bool(y)) => return x.partial_cmp(y),
             _ => return None,
@@ -110,21 +348,121 @@ impl PartialOrd for LiteralType {
     }
 }
 
+// Compares an integer with a float exactly rather than rounding the integer to the
+// nearest f64, which would make 2^53 + 1 equal to 2^53. NaN is unordered.
+fn compare_integer_float(x: &BigInt, y: f64) -> Option<Ordering> {
+    if y.is_nan() {
+        return None;
+    }
+    if y.is_infinite() {
+        return Some(if y > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+    let whole = y.floor();
+    match x.cmp(&BigInt::from_f64(whole)?) {
+        Ordering::Equal if y > whole => return Some(Ordering::Less),
+        ordering => return Some(ordering),
+    }
+}
+
+// how `print` shows a value: strings without quotes, the rest as they appear inside a list
+impl fmt::Display for LiteralType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralType::String(s) => write!(f, "{}", s),
+            _ => self.fmt_nested(f, &mut vec![]),
+        }
+    }
+}
+
 impl fmt::Debug for LiteralType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LiteralType::String(s) => write!(f, "{:?}", s),
-            LiteralType::Float(d) => write!(f, "{}", d),
-            LiteralType::Bool(b) => write!(f, "{}", b),
             LiteralType::Null => write!(f, "{:?}", "null"),
+            _ => self.fmt_nested(f, &mut vec![]),
         }
     }
 }
 
 impl LiteralType {
-    pub fn get_number(&self, line: &i32) -> Result<f32, RuntimeError> {
+    // Strings are quoted so their boundaries stay visible inside lists and maps.
+    // `printing` holds the lists and maps currently being written, so a container that
+    // contains itself prints as `[...]` or `{...}` instead of overflowing the stack.
+    fn fmt_nested(&self, f: &mut fmt::Formatter<'_>, printing: &mut Vec<usize>) -> fmt::Result {
+        match self {
+            LiteralType::String(s) => write!(f, "{:?}", s),
+            LiteralType::Int(i) => write!(f, "{}", i),
+            LiteralType::BigInt(i) => write!(f, "{}", i),
+            // `{:?}` keeps the `.0` on integral floats and uses exponent form for huge or tiny ones
+            LiteralType::Float(d) => write!(f, "{:?}", d),
+            LiteralType::Bool(b) => write!(f, "{}", b),
+            LiteralType::Function(func) => write!(f, "{}", func.describe()),
+            LiteralType::Native(n) => write!(f, "<native fn {}>", n.name),
+            LiteralType::Class(c) => write!(f, "{}", c.name),
+            LiteralType::Instance(i) => write!(f, "{} instance", i.borrow().class.name),
+            LiteralType::List(l) => {
+                let id = Rc::as_ptr(l) as usize;
+                if printing.contains(&id) {
+                    return write!(f, "[...]");
+                }
+                printing.push(id);
+                write!(f, "[")?;
+                for (i, e) in l.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    e.fmt_nested(f, printing)?;
+                }
+                printing.pop();
+                write!(f, "]")
+            }
+            LiteralType::Tuple(t) => {
+                write!(f, "(")?;
+                for (i, e) in t.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    e.fmt_nested(f, printing)?;
+                }
+                write!(f, ")")
+            }
+            LiteralType::Map(m) => {
+                let id = Rc::as_ptr(m) as usize;
+                if printing.contains(&id) {
+                    return write!(f, "{{...}}");
+                }
+                printing.push(id);
+                write!(f, "{{")?;
+                for (i, (k, v)) in m.borrow().entries().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    k.fmt_nested(f, printing)?;
+                    write!(f, ": ")?;
+                    v.fmt_nested(f, printing)?;
+                }
+                printing.pop();
+                write!(f, "}}")
+            }
+            LiteralType::Null => write!(f, "nil"),
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        return matches!(
+            self,
+            LiteralType::Int(_) | LiteralType::BigInt(_) | LiteralType::Float(_)
+        );
+    }
+
+    pub fn get_number(&self, line: &i32) -> Result<f64, RuntimeError> {
         match self {
-            LiteralType::Float(f) => return Ok(f.clone()),
+            LiteralType::Int(i) => return Ok(*i as f64),
+            LiteralType::BigInt(i) => return Ok(i.to_f64()),
+            LiteralType::Float(f) => return Ok(*f),
             _ => {
                 return Err(RuntimeError::error(
                     *line,
@@ -134,6 +472,107 @@ impl LiteralType {
         }
     }
 
+    // Two integers stay integers: when `int_op` fails (on overflow, or a zero
+    // divisor which `big_op` then reports) or either side is already a big
+    // integer, the operation is done with `big_op`. Anything else is done in
+    // floating point.
+    fn arithmetic(
+        self,
+        rhs: LiteralType,
+        int_op: fn(i64, i64) -> Option<i64>,
+        big_op: fn(&BigInt, &BigInt) -> Result<BigInt, String>,
+        float_op: fn(f64, f64) -> f64,
+    ) -> Result<LiteralType, String> {
+        match (&self, &rhs) {
+            (LiteralType::Int(x), LiteralType::Int(y)) => match int_op(*x, *y) {
+                Some(result) => return Ok(LiteralType::Int(result)),
+                None => {
+                    let result = big_op(&BigInt::from_i64(*x), &BigInt::from_i64(*y))?;
+                    return Ok(LiteralType::BigInt(result));
+                }
+            },
+            _ => {
+                if let (Some(x), Some(y)) = (self.as_big_int(), rhs.as_big_int()) {
+                    return Ok(LiteralType::BigInt(big_op(&x, &y)?));
+                }
+                let (x, y) = match (self.as_float(), rhs.as_float()) {
+                    (Some(x), Some(y)) => (x, y),
+                    _ => return Err(String::from("Operands must be numbers.")),
+                };
+                return Ok(LiteralType::Float(float_op(x, y)));
+            }
+        }
+    }
+
+    // Integer powers with a non-negative exponent stay integers (growing into a
+    // big integer when needed); anything else is done in floating point.
+    pub fn pow(self, rhs: LiteralType) -> Result<LiteralType, String> {
+        if let (Some(base), Some(exponent)) = (self.as_big_int(), rhs.as_big_int()) {
+            if !exponent.is_negative() {
+                let exponent = match exponent.to_i64().and_then(|e| u32::try_from(e).ok()) {
+                    Some(e) => e,
+                    None => return Err(String::from("Exponent is too large.")),
+                };
+                if let LiteralType::Int(x) = self {
+                    if let Some(result) = x.checked_pow(exponent) {
+                        return Ok(LiteralType::Int(result));
+                    }
+                }
+                return Ok(LiteralType::BigInt(base.pow(exponent)));
+            }
+        }
+        match (self.as_float(), rhs.as_float()) {
+            (Some(x), Some(y)) => return Ok(LiteralType::Float(x.powf(y))),
+            _ => return Err(String::from("Operands must be numbers.")),
+        }
+    }
+
+    fn bitwise(
+        self,
+        rhs: LiteralType,
+        int_op: fn(i64, i64) -> i64,
+        big_op: fn(u32, u32) -> u32,
+    ) -> Result<LiteralType, String> {
+        if let (LiteralType::Int(x), LiteralType::Int(y)) = (&self, &rhs) {
+            return Ok(LiteralType::Int(int_op(*x, *y)));
+        }
+        match (self.as_big_int(), rhs.as_big_int()) {
+            (Some(x), Some(y)) => return Ok(LiteralType::BigInt(x.bitwise(&y, big_op))),
+            _ => return Err(String::from("Bitwise operands must be integers.")),
+        }
+    }
+
+    fn shift_amount(value: &LiteralType, amount: &LiteralType) -> Result<usize, String> {
+        let amount = match (value.as_big_int(), amount.as_big_int()) {
+            (Some(_), Some(amount)) => amount,
+            _ => return Err(String::from("Bitwise operands must be integers.")),
+        };
+        if amount.is_negative() {
+            return Err(String::from("Shift amount can't be negative."));
+        }
+        match amount.to_i64().and_then(|a| u32::try_from(a).ok()) {
+            Some(bits) => return Ok(bits as usize),
+            None => return Err(String::from("Shift amount is too large.")),
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            LiteralType::Int(i) => return Some(*i as f64),
+            LiteralType::BigInt(i) => return Some(i.to_f64()),
+            LiteralType::Float(f) => return Some(*f),
+            _ => return None,
+        }
+    }
+
+    fn as_big_int(&self) -> Option<BigInt> {
+        match self {
+            LiteralType::Int(i) => return Some(BigInt::from_i64(*i)),
+            LiteralType::BigInt(i) => return Some(i.clone()),
+            _ => return None,
+        }
+    }
+
     pub fn get_string(&self, line: &i32) -> Result<String, String> {
         match self {
             LiteralType::String(f) => return Ok(f.clone()),
@@ -146,7 +585,9 @@ lazy_static! {
     pub static ref KEYWORDS: HashMap<&'static str, TokenType> = {
         let mut map = HashMap::new();
         map.insert("and", TokenType::AND);
+        map.insert("break", TokenType::BREAK);
         map.insert("class", TokenType::CLASS);
+        map.insert("continue", TokenType::CONTINUE);
         map.insert("else", TokenType::ELSE);
         map.insert("false", TokenType::FALSE);
         map.insert("for", TokenType::FOR);